@@ -89,6 +89,106 @@ impl Arg {
     }
 }
 
+/// The subset of the option schema that `Args::new` needs in order to parse structurally:
+/// which flags expect a value (and therefore may consume the following token as `--foo value`
+/// or `-l value`). This is threaded in at construction time because `Args::new` has no access
+/// to the full option registry.
+#[derive(Clone, Debug, Default)]
+pub struct KnownFlags {
+    // Value-taking long flags, stored in their dash-joined lowercase form without leading dashes
+    // (e.g. "changed-since", "test-output"). Both implicit and explicit-scope spellings should be
+    // registered here.
+    value_flags: HashSet<String>,
+    // Value-taking short flags, by their single letter.
+    value_shorts: HashSet<char>,
+    // Boolean short flags, by their single letter. Used to validate short-flag clusters
+    // (`-xvf` == `-x -v -f`).
+    bool_shorts: HashSet<char>,
+    // When set, a dash-leading token that parses as a numeric literal (e.g. `-5`, `-5.0`) is
+    // treated as a value/spec rather than a short flag, so int/float (list) options can receive
+    // negative numbers on the command line.
+    allow_negative_numbers: bool,
+}
+
+impl KnownFlags {
+    pub fn new(
+        value_flags: HashSet<String>,
+        value_shorts: HashSet<char>,
+        bool_shorts: HashSet<char>,
+        allow_negative_numbers: bool,
+    ) -> Self {
+        Self {
+            value_flags,
+            value_shorts,
+            bool_shorts,
+            allow_negative_numbers,
+        }
+    }
+
+    /// Whether `token` (a dash-leading arg) should be read as a numeric literal rather than a
+    /// flag, under the opt-in negative-numbers mode.
+    fn is_numeric_value(&self, token: &str) -> bool {
+        self.allow_negative_numbers && token.parse::<f64>().is_ok()
+    }
+
+    fn long_takes_value(&self, flag: &str) -> bool {
+        self.value_flags
+            .contains(&flag.trim_start_matches('-').to_ascii_lowercase())
+    }
+
+    fn short_takes_value(&self, short: char) -> bool {
+        self.value_shorts.contains(&short)
+    }
+
+    /// Try to expand a short-flag cluster (the characters after the leading `-`) into individual
+    /// flags. Every leading character must be a registered boolean short (expanded to a
+    /// value-less, i.e. true, flag); if a character is a value-taking short, the remainder of the
+    /// cluster (minus an optional `=`) becomes that option's value, exactly like `-ldebug`.
+    ///
+    /// Returns `None` when the cluster can't be validated at all (an unknown short), so the caller
+    /// falls back to the legacy single-flag behavior. When a value-taking short ends the cluster
+    /// with an empty remainder (e.g. the `l` in `-vl foo`), its value must instead be consumed
+    /// from the next, space-separated token: the already-expanded leading bools are returned
+    /// alongside that trailing flag so the caller doesn't have to re-derive or discard them.
+    fn expand_short_cluster(&self, cluster: &str) -> Option<ClusterExpansion> {
+        let mut out = vec![];
+        let mut chars = cluster.char_indices();
+        while let Some((i, c)) = chars.next() {
+            if self.bool_shorts.contains(&c) {
+                out.push((format!("-{}", c), None));
+            } else if self.value_shorts.contains(&c) {
+                let rest = &cluster[i + c.len_utf8()..];
+                let rest = rest.strip_prefix('=').unwrap_or(rest);
+                if rest.is_empty() {
+                    return Some(ClusterExpansion::TrailingValueNeeded {
+                        leading: out,
+                        trailing_flag: format!("-{}", c),
+                    });
+                }
+                out.push((format!("-{}", c), Some(rest.to_string())));
+                return Some(ClusterExpansion::Complete(out));
+            } else {
+                // An unrecognized short means we can't validate the cluster.
+                return None;
+            }
+        }
+        Some(ClusterExpansion::Complete(out))
+    }
+}
+
+/// The result of successfully validating a short-flag cluster; see `KnownFlags::expand_short_cluster`.
+enum ClusterExpansion {
+    /// Every short in the cluster resolved to a flag, with a value where applicable.
+    Complete(Vec<(String, Option<String>)>),
+    /// The cluster validated up to a trailing value-taking short with nothing left to be its
+    /// value; `leading` are the already-expanded flags before it, and `trailing_flag` still needs
+    /// its value consumed from the next token.
+    TrailingValueNeeded {
+        leading: Vec<(String, Option<String>)>,
+        trailing_flag: String,
+    },
+}
+
 #[derive(Debug)]
 pub struct Args {
     // The arg strings this struct was instantiated with.
@@ -103,6 +203,13 @@ impl Args {
     // Create an Args instance with the provided args, which must *not* include the
     // argv[0] process name.
     pub fn new<I: IntoIterator<Item = String>>(arg_strs: I) -> Self {
+        Self::new_with_flags(arg_strs, &KnownFlags::default())
+    }
+
+    // Create an Args instance, consulting `known` to decide which flags may consume the following
+    // token as a space-separated value (`--foo value`, `-l value`). The `--foo=bar`/`-lbar` fast
+    // paths are unchanged.
+    pub fn new_with_flags<I: IntoIterator<Item = String>>(arg_strs: I, known: &KnownFlags) -> Self {
         let arg_strs = arg_strs.into_iter().collect::<Vec<_>>();
         let mut args: Vec<Arg> = vec![];
         let mut passthrough_args: Option<Vec<String>> = None;
@@ -117,28 +224,85 @@ impl Args {
             } else if arg_str.starts_with("--") {
                 let mut components = arg_str.splitn(2, '=');
                 let flag = components.next().unwrap();
+                let mut value = components.next().map(str::to_string);
+                // A bare `--foo` for a value-taking flag consumes the next token as its value,
+                // rather than emitting a bare boolean and misclassifying the token as a spec. But
+                // `--` itself is never a value: the passthrough-args delimiter must still win, so
+                // peek rather than unconditionally consuming.
+                if value.is_none()
+                    && known.long_takes_value(flag)
+                    && args_iter.clone().next().map(String::as_str) != Some("--")
+                {
+                    value = args_iter.next().cloned();
+                }
                 args.push(Arg {
                     context: scope.clone(),
                     flag: flag.to_string(),
-                    value: components.next().map(str::to_string),
+                    value,
                 });
             } else if arg_str.starts_with('-') && arg_str.len() >= 2 {
+                if known.is_numeric_value(arg_str) {
+                    // A negative number standing on its own is a positional value, not a flag.
+                    // Leave the scope untouched (unlike a spec, which reverts to Global) since a
+                    // numeric literal never names a goal.
+                    continue;
+                }
+                if let Some(cluster) = known.expand_short_cluster(&arg_str[1..]) {
+                    let (leading, trailing_flag) = match cluster {
+                        ClusterExpansion::Complete(flags) => (flags, None),
+                        ClusterExpansion::TrailingValueNeeded {
+                            leading,
+                            trailing_flag,
+                        } => (leading, Some(trailing_flag)),
+                    };
+                    for (flag, value) in leading {
+                        args.push(Arg {
+                            context: scope.clone(),
+                            flag,
+                            value,
+                        });
+                    }
+                    if let Some(flag) = trailing_flag {
+                        // The trailing value-taking short had nothing left in the cluster to be
+                        // its value (e.g. the `l` in `-vl foo`); consume the next token instead,
+                        // unless that token is the `--` passthrough delimiter.
+                        let value = if args_iter.clone().next().map(String::as_str) != Some("--")
+                        {
+                            args_iter.next().cloned()
+                        } else {
+                            None
+                        };
+                        args.push(Arg {
+                            context: scope.clone(),
+                            flag,
+                            value,
+                        });
+                    }
+                    continue;
+                }
                 let (flag, mut value) = arg_str.split_at(2);
                 // We support -ldebug and -l=debug, so strip that extraneous equals sign.
                 if let Some(stripped) = value.strip_prefix('=') {
                     value = stripped;
                 }
+                let value = if !value.is_empty() {
+                    Some(value.to_string())
+                } else if known.short_takes_value(flag.chars().nth(1).unwrap())
+                    && args_iter.clone().next().map(String::as_str) != Some("--")
+                {
+                    // A bare `-l` for a value-taking short flag consumes the next token, unless
+                    // that token is the `--` passthrough delimiter.
+                    args_iter.next().cloned()
+                } else {
+                    None
+                };
                 args.push(Arg {
                     context: scope.clone(),
                     flag: flag.to_string(),
-                    value: if value.is_empty() {
-                        None
-                    } else {
-                        Some(value.to_string())
-                    },
+                    value,
                 });
             } else if is_valid_scope_name(arg_str) {
-                scope = Scope::Scope(arg_str.to_string())
+                scope = Scope::named(arg_str)
             } else {
                 // The arg is a spec, so revert to global context for any trailing flags.
                 scope = Scope::Global;
@@ -163,6 +327,141 @@ impl Args {
     }
 }
 
+/// Normalize a flag to its dash-joined, lowercase form for fuzzy comparison.
+/// Both typed flags (`--Changd-Since`) and candidate displays (`--changed-since`) are
+/// reduced to `changd-since` / `changed-since` so leading dashes and case don't skew scores.
+fn normalize_flag(flag: &str) -> String {
+    flag.trim_start_matches('-').to_ascii_lowercase()
+}
+
+/// Damerau-Levenshtein edit distance, where insertions, deletions, substitutions, and
+/// adjacent transpositions each cost 1. Used to rank candidate flags against a typo.
+fn damerau_levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let (m, n) = (a.len(), b.len());
+    if m == 0 {
+        return n;
+    }
+    if n == 0 {
+        return m;
+    }
+
+    // Full matrix, since transpositions need the row two above the current one.
+    let mut d = vec![vec![0usize; n + 1]; m + 1];
+    for i in 0..=m {
+        d[i][0] = i;
+    }
+    for j in 0..=n {
+        d[0][j] = j;
+    }
+    for i in 1..=m {
+        for j in 1..=n {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            let mut best = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(d[i - 2][j - 2] + 1);
+            }
+            d[i][j] = best;
+        }
+    }
+    d[m][n]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn damerau_levenshtein_basic_distances() {
+        assert_eq!(damerau_levenshtein("", ""), 0);
+        assert_eq!(damerau_levenshtein("changed-since", "changed-since"), 0);
+        assert_eq!(damerau_levenshtein("changd-since", "changed-since"), 1);
+    }
+
+    #[test]
+    fn damerau_levenshtein_scores_transposition_as_one_edit() {
+        // "ab" -> "ba" is a single adjacent transposition, which plain Levenshtein would
+        // instead charge two substitutions for.
+        assert_eq!(damerau_levenshtein("ab", "ba"), 1);
+        assert_eq!(damerau_levenshtein("changed-scine", "changed-since"), 1);
+    }
+
+    #[test]
+    fn normalize_flag_strips_dashes_and_case() {
+        assert_eq!(normalize_flag("--Changed-Since"), "changed-since");
+        assert_eq!(normalize_flag("-l"), "l");
+    }
+
+    #[test]
+    fn double_dash_passthrough_wins_over_value_consuming_long_flag() {
+        let mut value_flags = HashSet::new();
+        value_flags.insert("foo".to_string());
+        let known = KnownFlags::new(value_flags, HashSet::new(), HashSet::new(), false);
+        let args = Args::new_with_flags(
+            vec!["--foo".to_string(), "--".to_string(), "bar".to_string()],
+            &known,
+        );
+        assert_eq!(args.passthrough_args, Some(vec!["bar".to_string()]));
+        assert_eq!(args.args.len(), 1);
+        assert_eq!(args.args[0].value, None);
+    }
+
+    #[test]
+    fn double_dash_passthrough_wins_over_value_consuming_short_flag() {
+        let mut value_shorts = HashSet::new();
+        value_shorts.insert('l');
+        let known = KnownFlags::new(HashSet::new(), value_shorts, HashSet::new(), false);
+        let args = Args::new_with_flags(
+            vec!["-l".to_string(), "--".to_string(), "bar".to_string()],
+            &known,
+        );
+        assert_eq!(args.passthrough_args, Some(vec!["bar".to_string()]));
+        assert_eq!(args.args.len(), 1);
+        assert_eq!(args.args[0].value, None);
+    }
+
+    #[test]
+    fn short_cluster_keeps_leading_bools_when_trailing_value_short_is_empty() {
+        // `-vl foo`, with `v` a bool short and `l` a value-taking short: `l`'s remainder in the
+        // cluster is empty, so its value must come from the next token, but the already-expanded
+        // `-v` must not be discarded (nor must `foo` be misread as `-v`'s value).
+        let mut bool_shorts = HashSet::new();
+        bool_shorts.insert('v');
+        let mut value_shorts = HashSet::new();
+        value_shorts.insert('l');
+        let known = KnownFlags::new(HashSet::new(), value_shorts, bool_shorts, false);
+        let args = Args::new_with_flags(
+            vec!["-vl".to_string(), "foo".to_string()],
+            &known,
+        );
+        assert_eq!(args.args.len(), 2);
+        assert_eq!(args.args[0].flag, "-v");
+        assert_eq!(args.args[0].value, None);
+        assert_eq!(args.args[1].flag, "-l");
+        assert_eq!(args.args[1].value, Some("foo".to_string()));
+    }
+
+    #[test]
+    fn short_cluster_trailing_value_short_still_respects_double_dash_passthrough() {
+        let mut bool_shorts = HashSet::new();
+        bool_shorts.insert('v');
+        let mut value_shorts = HashSet::new();
+        value_shorts.insert('l');
+        let known = KnownFlags::new(HashSet::new(), value_shorts, bool_shorts, false);
+        let args = Args::new_with_flags(
+            vec!["-vl".to_string(), "--".to_string(), "foo".to_string()],
+            &known,
+        );
+        assert_eq!(args.passthrough_args, Some(vec!["foo".to_string()]));
+        assert_eq!(args.args.len(), 2);
+        assert_eq!(args.args[1].flag, "-l");
+        assert_eq!(args.args[1].value, None);
+    }
+}
+
 pub(crate) struct ArgsTracker {
     unconsumed_args: Mutex<HashSet<Arg>>,
 }
@@ -282,6 +581,59 @@ impl ArgsReader {
             Ok(Some(edits))
         }
     }
+
+    /// For every unconsumed flag, compute up to three "did you mean?" suggestions drawn from the
+    /// known `OptionId`s. Candidates from the same scope as the typed flag rank first, with
+    /// global-scope candidates as the fallback after them, so e.g. `fmt --chanded` suggests `fmt`
+    /// options before unrelated ones. Candidates are kept when their Damerau-Levenshtein distance
+    /// to the typed flag is `<= max(1, len/3)`; within each of the two groups they are sorted
+    /// ascending by distance and lexicographically.
+    pub fn get_unconsumed_flag_suggestions(
+        &self,
+        known_by_scope: &HashMap<Scope, Vec<OptionId>>,
+    ) -> HashMap<Scope, Vec<(String, Vec<String>)>> {
+        let mut ret: HashMap<Scope, Vec<(String, Vec<String>)>> = HashMap::new();
+        for arg in self.tracker.unconsumed_args.lock().iter() {
+            let typed = normalize_flag(&arg.flag);
+            let threshold = std::cmp::max(1, typed.chars().count() / 3);
+
+            // Candidates from the arg's own scope (tier 0), then global as a fallback (tier 1).
+            let global = if arg.context == Scope::Global {
+                None
+            } else {
+                known_by_scope.get(&Scope::Global)
+            };
+            let candidates = known_by_scope
+                .get(&arg.context)
+                .into_iter()
+                .flatten()
+                .map(|id| (0usize, id))
+                .chain(global.into_iter().flatten().map(|id| (1usize, id)));
+
+            let mut scored: Vec<(usize, usize, String)> = candidates
+                .map(|(tier, id)| (tier, self.display(id)))
+                .map(|(tier, display)| {
+                    let distance = damerau_levenshtein(&typed, &normalize_flag(&display));
+                    (tier, distance, display)
+                })
+                .filter(|(_, distance, _)| *distance <= threshold)
+                .collect();
+            scored.sort();
+            scored.dedup_by(|a, b| a.2 == b.2);
+
+            let suggestions: Vec<String> =
+                scored.into_iter().take(3).map(|(_, _, display)| display).collect();
+            if !suggestions.is_empty() {
+                ret.entry(arg.context.clone())
+                    .or_default()
+                    .push((arg.flag.clone(), suggestions));
+            }
+        }
+        for entry in ret.values_mut() {
+            entry.sort();
+        }
+        ret
+    }
 }
 
 impl OptionsSource for ArgsReader {
@@ -290,7 +642,7 @@ impl OptionsSource for ArgsReader {
             "--{}{}",
             match &id.scope {
                 Scope::Global => "".to_string(),
-                Scope::Scope(scope) => format!("{}-", scope.to_ascii_lowercase()),
+                Scope::Scope(scope) => format!("{}-", scope.as_str().to_ascii_lowercase()),
             },
             id.name("-", NameTransform::ToLower)
         )
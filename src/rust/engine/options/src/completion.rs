@@ -0,0 +1,285 @@
+// Copyright 2024 Pants project contributors (see CONTRIBUTORS.md).
+// Licensed under the Apache License, Version 2.0 (see LICENSE).
+
+use std::collections::HashMap;
+use std::fmt::Write;
+
+use super::id::{NameTransform, OptionId};
+use super::scope::Scope;
+
+/// The shells for which completion scripts can be generated.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    pub fn parse(name: &str) -> Result<Shell, String> {
+        match name {
+            "bash" => Ok(Shell::Bash),
+            "zsh" => Ok(Shell::Zsh),
+            "fish" => Ok(Shell::Fish),
+            other => Err(format!("Unknown shell for completions: {}", other)),
+        }
+    }
+}
+
+/// A single completable flag, flattened from an `OptionId` so that completion generation does not
+/// depend on the full option machinery at completion time.
+#[derive(Clone, Debug)]
+struct Flag {
+    /// The implicit `--flag` form (without a scope prefix).
+    implicit: String,
+    /// The explicit `--scope-flag` form, for options registered in a non-global scope.
+    explicit: Option<String>,
+    /// The `-s` short form, if any.
+    short: Option<String>,
+    /// The `--no-flag` negation, present only for bools.
+    negation: Option<String>,
+    /// Whether the flag expects an `=value` (false for bools).
+    takes_value: bool,
+}
+
+impl Flag {
+    fn from_id(id: &OptionId, takes_value: bool) -> Flag {
+        let name = id.name("-", NameTransform::ToLower);
+        let implicit = format!("--{}", name);
+        let explicit = match &id.scope {
+            Scope::Global => None,
+            Scope::Scope(scope) => {
+                Some(format!("--{}-{}", scope.as_str().to_ascii_lowercase(), name))
+            }
+        };
+        let short = id.short_name.as_ref().map(|s| format!("-{}", s.as_ref()));
+        let negation = if takes_value {
+            None
+        } else {
+            Some(format!("--no-{}", name))
+        };
+        Flag {
+            implicit,
+            explicit,
+            short,
+            negation,
+            takes_value,
+        }
+    }
+
+    /// All of the dash-leading forms this flag can be typed as. Value-taking forms are suffixed
+    /// with `=` so shells complete straight into `--flag=` rather than offering a bare flag that
+    /// would need a separate, space-separated value typed after it.
+    fn forms(&self) -> Vec<String> {
+        let mut forms = vec![self.value_form(&self.implicit)];
+        forms.extend(self.explicit.as_deref().map(|f| self.value_form(f)));
+        forms.extend(self.short.as_deref().map(|f| self.value_form(f)));
+        forms.extend(self.negation.clone());
+        forms
+    }
+
+    fn value_form(&self, flag: &str) -> String {
+        if self.takes_value {
+            format!("{}=", flag)
+        } else {
+            flag.to_string()
+        }
+    }
+}
+
+/// A flag together with whether it expects a value, as declared by the caller.
+pub struct OptionSpec {
+    pub id: OptionId,
+    pub takes_value: bool,
+}
+
+/// Generate a completion script for the given shell.
+///
+/// `scopes` is the set of registered goal/scope tokens (e.g. `test`, `fmt`); `options_by_scope`
+/// maps each scope (plus `Scope::Global`) to the options registered there. The returned script is
+/// plain text that a user can `source`. Completions are context-aware: once a scope token has been
+/// seen on the command line, that scope's flags become available.
+pub fn generate(
+    shell: Shell,
+    scopes: &[String],
+    options_by_scope: &HashMap<Scope, Vec<OptionSpec>>,
+) -> String {
+    let flags_by_scope: HashMap<&Scope, Vec<Flag>> = options_by_scope
+        .iter()
+        .map(|(scope, specs)| {
+            (
+                scope,
+                specs
+                    .iter()
+                    .map(|spec| Flag::from_id(&spec.id, spec.takes_value))
+                    .collect(),
+            )
+        })
+        .collect();
+    match shell {
+        Shell::Bash => generate_bash(scopes, &flags_by_scope),
+        Shell::Zsh => generate_zsh(scopes, &flags_by_scope),
+        Shell::Fish => generate_fish(scopes, &flags_by_scope),
+    }
+}
+
+fn global_forms(flags_by_scope: &HashMap<&Scope, Vec<Flag>>) -> Vec<String> {
+    flags_by_scope
+        .get(&Scope::Global)
+        .into_iter()
+        .flatten()
+        .flat_map(Flag::forms)
+        .collect()
+}
+
+fn scope_forms(flags_by_scope: &HashMap<&Scope, Vec<Flag>>, scope: &str) -> Vec<String> {
+    flags_by_scope
+        .get(&Scope::named(scope))
+        .into_iter()
+        .flatten()
+        .flat_map(Flag::forms)
+        .collect()
+}
+
+fn generate_bash(scopes: &[String], flags_by_scope: &HashMap<&Scope, Vec<Flag>>) -> String {
+    let mut out = String::new();
+    out.push_str("# bash completion for pants (generated)\n");
+    out.push_str("_pants() {\n");
+    out.push_str("  local cur scope word\n");
+    out.push_str("  cur=\"${COMP_WORDS[COMP_CWORD]}\"\n");
+    out.push_str("  scope=\"\"\n");
+    out.push_str("  for word in \"${COMP_WORDS[@]:1:COMP_CWORD-1}\"; do\n");
+    write!(out, "    case \"$word\" in {}) scope=\"$word\";; esac\n", scopes.join("|"))
+        .unwrap();
+    out.push_str("  done\n");
+    writeln!(out, "  local goals=\"{}\"", scopes.join(" ")).unwrap();
+    writeln!(out, "  local global=\"{}\"", global_forms(flags_by_scope).join(" ")).unwrap();
+    out.push_str("  local flags=\"$global\"\n");
+    out.push_str("  case \"$scope\" in\n");
+    for scope in scopes {
+        let forms = scope_forms(flags_by_scope, scope);
+        writeln!(out, "    {}) flags=\"$flags {}\";;", scope, forms.join(" ")).unwrap();
+    }
+    out.push_str("  esac\n");
+    out.push_str("  if [[ \"$cur\" == -* ]]; then\n");
+    out.push_str("    COMPREPLY=( $(compgen -W \"$flags\" -- \"$cur\") )\n");
+    out.push_str("  else\n");
+    out.push_str("    COMPREPLY=( $(compgen -W \"$goals\" -- \"$cur\") )\n");
+    out.push_str("  fi\n");
+    out.push_str("}\n");
+    out.push_str("complete -o nospace -F _pants pants\n");
+    out
+}
+
+fn generate_zsh(scopes: &[String], flags_by_scope: &HashMap<&Scope, Vec<Flag>>) -> String {
+    let mut out = String::new();
+    out.push_str("#compdef pants\n");
+    out.push_str("# zsh completion for pants (generated)\n");
+    out.push_str("_pants() {\n");
+    out.push_str("  local scope=\"\"\n");
+    out.push_str("  local w\n");
+    out.push_str("  for w in ${words[2,CURRENT-1]}; do\n");
+    write!(out, "    case \"$w\" in {}) scope=\"$w\";; esac\n", scopes.join("|"))
+        .unwrap();
+    out.push_str("  done\n");
+    writeln!(out, "  local goals=({})", scopes.join(" ")).unwrap();
+    writeln!(out, "  local global=({})", forms_quoted(&global_forms(flags_by_scope))).unwrap();
+    out.push_str("  local -a flags=($global)\n");
+    out.push_str("  case \"$scope\" in\n");
+    for scope in scopes {
+        let forms = scope_forms(flags_by_scope, scope);
+        writeln!(out, "    {}) flags+=({});;", scope, forms_quoted(&forms)).unwrap();
+    }
+    out.push_str("  esac\n");
+    out.push_str("  if [[ \"$PREFIX\" == -* ]]; then\n");
+    out.push_str("    compadd -- $flags\n");
+    out.push_str("  else\n");
+    out.push_str("    compadd -- $goals\n");
+    out.push_str("  fi\n");
+    out.push_str("}\n");
+    out.push_str("_pants \"$@\"\n");
+    out
+}
+
+fn generate_fish(scopes: &[String], flags_by_scope: &HashMap<&Scope, Vec<Flag>>) -> String {
+    let mut out = String::new();
+    out.push_str("# fish completion for pants (generated)\n");
+    out.push_str("function __pants_scope\n");
+    writeln!(out, "  set -l goals {}", scopes.join(" ")).unwrap();
+    out.push_str("  for w in (commandline -opc)[2..-1]\n");
+    out.push_str("    if contains -- $w $goals; echo $w; end\n");
+    out.push_str("  end\n");
+    out.push_str("end\n");
+    // Goal/scope tokens when no scope has been selected yet.
+    for scope in scopes {
+        writeln!(
+            out,
+            "complete -c pants -n 'test -z (__pants_scope)' -a '{}'",
+            scope
+        )
+        .unwrap();
+    }
+    // Global flags are always available.
+    for flag in global_forms(flags_by_scope) {
+        writeln!(out, "complete -c pants -a '{}'", flag).unwrap();
+    }
+    // Scope-specific flags, guarded by the selected scope.
+    for scope in scopes {
+        for flag in scope_forms(flags_by_scope, scope) {
+            writeln!(
+                out,
+                "complete -c pants -n 'contains -- {} (__pants_scope)' -a '{}'",
+                scope, flag
+            )
+            .unwrap();
+        }
+    }
+    out
+}
+
+fn forms_quoted(forms: &[String]) -> String {
+    forms
+        .iter()
+        .map(|f| format!("'{}'", f))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn value_taking_flag_forms_get_an_equals_suffix() {
+        let flag = Flag {
+            implicit: "--changed-since".to_string(),
+            explicit: Some("--changed-changed-since".to_string()),
+            short: Some("-c".to_string()),
+            negation: None,
+            takes_value: true,
+        };
+        assert_eq!(
+            flag.forms(),
+            vec![
+                "--changed-since=".to_string(),
+                "--changed-changed-since=".to_string(),
+                "-c=".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn boolean_flag_forms_have_no_equals_suffix_and_include_negation() {
+        let flag = Flag {
+            implicit: "--cleanup".to_string(),
+            explicit: None,
+            short: None,
+            negation: Some("--no-cleanup".to_string()),
+            takes_value: false,
+        };
+        assert_eq!(
+            flag.forms(),
+            vec!["--cleanup".to_string(), "--no-cleanup".to_string()]
+        );
+    }
+}
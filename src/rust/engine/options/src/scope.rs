@@ -1,41 +1,295 @@
 // Copyright 2021 Pants project contributors (see CONTRIBUTORS.md).
 // Licensed under the Apache License, Version 2.0 (see LICENSE).
 
+use std::collections::HashMap;
+use std::iter::once;
+
 use lazy_static::lazy_static;
+use parking_lot::RwLock;
 
 use regex::Regex;
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq)]
-pub enum Scope {
-    Global,
-    Scope(String),
+/// A `Copy` handle to an interned scope/goal name, with O(1) equality and hashing by id. Two names
+/// that differ only in their separators (e.g. `my-tool` and `my_tool`) canonicalize to the same
+/// symbol, so they compare equal while each keeps the spelling it was first typed with for display.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ScopeSymbol(u32);
+
+impl ScopeSymbol {
+    /// The scope name as it was first typed (original separators preserved), for display.
+    pub fn as_str(&self) -> &'static str {
+        INTERNER.read().displays[self.0 as usize]
+    }
+
+    /// The canonical scope name (separators normalized), used as the equality/hashing key.
+    pub fn canonical(&self) -> &'static str {
+        INTERNER.read().names[self.0 as usize]
+    }
+}
+
+// Ordered by canonical name rather than by (first-seen) interning id, so that ordered uses of
+// `Scope` (e.g. `BTreeMap` iteration, sorted diagnostics) are stable across runs and across
+// pantsd restarts rather than depending on the order names happened to be interned in.
+impl PartialOrd for ScopeSymbol {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for ScopeSymbol {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.canonical().cmp(other.canonical())
+    }
+}
+
+/// Normalize a scope name's separators: treat `_` and `-` as the same separator and collapse runs
+/// of them into a single `-`. `validate_scope_name` rejects a leading or trailing `-`, but accepts
+/// a leading or trailing `_`, which canonicalizes to a leading or trailing `-` here.
+fn canonicalize(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut prev_separator = false;
+    for ch in name.chars() {
+        let ch = if ch == '_' { '-' } else { ch };
+        if ch == '-' {
+            if prev_separator {
+                continue;
+            }
+            prev_separator = true;
+        } else {
+            prev_separator = false;
+        }
+        out.push(ch);
+    }
+    out
+}
+
+/// The interner is bounded to this many distinct canonical names. Every registered goal and
+/// subsystem scope is interned once at startup (via `GoalInfo::new`), so real usage stays far
+/// below this; it exists to bound how much a long-lived pantsd daemon can leak in response to
+/// repeated syntactically-valid-but-unregistered scope tokens (typos) arriving on the CLI over
+/// the daemon's lifetime. Once hit, interning a new name panics rather than folding it into a
+/// shared placeholder symbol: a shared symbol would make two genuinely different scope names
+/// compare equal, which is a silent option-resolution correctness bug, not a contained one.
+const MAX_INTERNED_SCOPES: usize = 4096;
+
+/// A tiny global string-interning table. Names are leaked to `'static` (there is a small, bounded
+/// set of distinct scope names over a process lifetime), so a `ScopeSymbol` can hand back a stable
+/// reference without copying. Symbols are keyed by the *canonical* name, which removes the
+/// per-parse `String` allocations that `Scope::Scope(String)` previously incurred and lets the hot
+/// `HashMap<Scope, _>` lookups compare and hash a single `u32` while still treating separator
+/// variants as one scope. Each id also remembers the original spelling first seen for it.
+struct Interner {
+    ids: HashMap<&'static str, u32>,
+    names: Vec<&'static str>,
+    displays: Vec<&'static str>,
+}
+
+impl Interner {
+    fn intern(&mut self, name: &str) -> ScopeSymbol {
+        let canonical = canonicalize(name);
+        if let Some(&id) = self.ids.get(canonical.as_str()) {
+            return ScopeSymbol(id);
+        }
+        assert!(
+            self.names.len() < MAX_INTERNED_SCOPES,
+            "Exceeded the maximum of {} distinct interned scope names while interning {:?}; \
+             refusing to fold it into an existing symbol, since that would make it silently \
+             compare equal to an unrelated scope",
+            MAX_INTERNED_SCOPES,
+            name,
+        );
+        let canonical: &'static str = Box::leak(canonical.into_boxed_str());
+        let display: &'static str = Box::leak(name.to_owned().into_boxed_str());
+        let id = self.names.len() as u32;
+        self.names.push(canonical);
+        self.displays.push(display);
+        self.ids.insert(canonical, id);
+        ScopeSymbol(id)
+    }
 }
 
 lazy_static! {
     // Note: must be aligned with the regex in src/python/pants/option/subsystem.py.
     static ref SCOPE_NAME_RE: Regex = Regex::new(r"^(?:[a-z0-9_])+(?:-(?:[a-z0-9_])+)*$").unwrap();
+
+    // Preallocate the well-known "GLOBAL" name so it gets a stable id 0, mirroring how
+    // interned-symbol tables reserve common identifiers up front.
+    static ref INTERNER: RwLock<Interner> = {
+        let mut interner = Interner {
+            ids: HashMap::new(),
+            names: Vec::new(),
+            displays: Vec::new(),
+        };
+        interner.intern("GLOBAL");
+        RwLock::new(interner)
+    };
 }
 
-pub(crate) fn is_valid_scope_name(name: &str) -> bool {
+/// Whether `name` is already in `canonicalize`'s normal form (no underscores, no repeated
+/// separators), so a lookup can use `name` as-is instead of allocating a canonical copy first.
+/// Registered goal/subsystem names, and the vast majority of scope names actually typed, already
+/// satisfy this.
+fn is_already_canonical(name: &str) -> bool {
+    if name.contains('_') {
+        return false;
+    }
+    let mut prev_separator = false;
+    for ch in name.chars() {
+        if ch == '-' {
+            if prev_separator {
+                return false;
+            }
+            prev_separator = true;
+        } else {
+            prev_separator = false;
+        }
+    }
+    true
+}
+
+/// Intern a scope name, returning its `Copy` symbol.
+pub fn intern_scope(name: &str) -> ScopeSymbol {
+    // Fast path: the canonical name is already interned. Skip `canonicalize`'s allocation
+    // entirely when `name` is already canonical, which is the common case for this hot,
+    // per-option-access lookup (`HashMap<Scope, _>`); only fall back to allocating a canonical
+    // copy when `name` actually needs separator normalization.
+    let id = if is_already_canonical(name) {
+        INTERNER.read().ids.get(name).copied()
+    } else {
+        INTERNER.read().ids.get(canonicalize(name).as_str()).copied()
+    };
+    if let Some(id) = id {
+        return ScopeSymbol(id);
+    }
+    INTERNER.write().intern(name)
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Scope {
+    Global,
+    Scope(ScopeSymbol),
+}
+
+// As with `ScopeSymbol`, order by canonical name rather than by variant/interning-id order, so
+// ordering doesn't depend on which scopes happened to be parsed or registered first.
+impl PartialOrd for Scope {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Scope {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.canonical_name().cmp(other.canonical_name())
+    }
+}
+
+/// The distinct reasons a scope name can be rejected, so callers can explain *why* a name is
+/// invalid rather than emitting a generic rejection.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ScopeNameError {
+    Empty,
+    ReservedWord(String),
+    InvalidLeadingChar,
+    InvalidChar { pos: usize, ch: char },
+    TrailingSeparator,
+    ConsecutiveSeparators,
+}
+
+impl std::fmt::Display for ScopeNameError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ScopeNameError::Empty => write!(f, "scope names may not be empty"),
+            ScopeNameError::ReservedWord(word) => {
+                write!(f, "`{}` is a reserved word and may not be a scope name", word)
+            }
+            ScopeNameError::InvalidLeadingChar => {
+                write!(f, "scope names may not begin with `-`")
+            }
+            ScopeNameError::InvalidChar { pos, ch } => write!(
+                f,
+                "scope names may not contain `{}` (at position {})",
+                ch, pos
+            ),
+            ScopeNameError::TrailingSeparator => {
+                write!(f, "scope names may not end with `-`")
+            }
+            ScopeNameError::ConsecutiveSeparators => {
+                write!(f, "scope names may not contain consecutive `-` separators")
+            }
+        }
+    }
+}
+
+/// Validate a scope name, returning a structured reason on rejection. The regex is kept as a
+/// fast-path acceptance check; the granular checks (modeled on layered package/registry name
+/// validation) run only when it fails, so the common case stays cheap.
+pub(crate) fn validate_scope_name(name: &str) -> Result<(), ScopeNameError> {
     // The exact string "pants" is not allowed as a scope name: if we encounter it on the
     // command line, it is part of the invocation: /path/to/python -m pants <actual args>.
-    SCOPE_NAME_RE.is_match(name) && name != "pants"
+    if name == "pants" {
+        return Err(ScopeNameError::ReservedWord("pants".to_owned()));
+    }
+    if SCOPE_NAME_RE.is_match(name) {
+        return Ok(());
+    }
+
+    if name.is_empty() {
+        return Err(ScopeNameError::Empty);
+    }
+    // Leading character: a separator (or anything not in the allowed set) is rejected first.
+    let first = name.chars().next().unwrap();
+    if first == '-' {
+        return Err(ScopeNameError::InvalidLeadingChar);
+    }
+    let mut prev_separator = false;
+    for (pos, ch) in name.char_indices() {
+        if ch == '-' {
+            if prev_separator {
+                return Err(ScopeNameError::ConsecutiveSeparators);
+            }
+            prev_separator = true;
+        } else if ch.is_ascii_lowercase() || ch.is_ascii_digit() || ch == '_' {
+            prev_separator = false;
+        } else {
+            return Err(ScopeNameError::InvalidChar { pos, ch });
+        }
+    }
+    if name.ends_with('-') {
+        return Err(ScopeNameError::TrailingSeparator);
+    }
+    Ok(())
+}
+
+pub(crate) fn is_valid_scope_name(name: &str) -> bool {
+    validate_scope_name(name).is_ok()
 }
 
 impl Scope {
     pub fn named(name: &str) -> Scope {
         match name {
             "" | "GLOBAL" => Scope::Global,
-            scope => Scope::Scope(scope.to_owned()),
+            scope => Scope::Scope(intern_scope(scope)),
         }
     }
 
+    /// The scope name as it was first typed (the spelling first interned for its canonical name),
+    /// with its original separators preserved, for display.
     pub fn name(&self) -> &str {
         match self {
             Scope::Global => "GLOBAL",
             Scope::Scope(scope) => scope.as_str(),
         }
     }
+
+    /// The canonical scope name, with separators normalized. Two scopes that differ only in their
+    /// separators share this key (and compare equal).
+    pub fn canonical_name(&self) -> &str {
+        match self {
+            Scope::Global => "GLOBAL",
+            Scope::Scope(scope) => scope.canonical(),
+        }
+    }
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -46,6 +300,66 @@ pub struct GoalInfo {
     pub aliases: Vec<String>,
 }
 
+/// Classic Levenshtein edit distance via the two-row dynamic-programming recurrence.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut curr = vec![0usize; b.len() + 1];
+    for (i, &ca) in a.iter().enumerate() {
+        curr[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let cost = if ca == cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j + 1] + 1).min(curr[j] + 1).min(prev[j] + cost);
+        }
+        std::mem::swap(&mut prev, &mut curr);
+    }
+    prev[b.len()]
+}
+
+/// Given an unrecognized goal/scope token, return up to `max_suggestions` of the closest valid
+/// scopes. Candidates are each `GoalInfo`'s `scope_name` and `aliases`, plus the known subsystem
+/// scopes. The distance threshold is proportional to the input length (`max(1, len/3)`);
+/// candidates whose length differs from the input by more than the threshold are short-circuited.
+/// Results are sorted by ascending distance, then lexicographically.
+pub fn suggest_scopes(
+    input: &str,
+    goals: &[GoalInfo],
+    subsystem_scopes: &[String],
+    max_suggestions: usize,
+) -> Vec<String> {
+    let input_len = input.chars().count();
+    let threshold = std::cmp::max(1, input_len / 3);
+
+    let candidates = goals
+        .iter()
+        .flat_map(|goal| once(&goal.scope_name).chain(goal.aliases.iter()))
+        .chain(subsystem_scopes.iter());
+
+    let mut scored: Vec<(usize, &str)> = candidates
+        .filter_map(|candidate| {
+            let candidate_len = candidate.chars().count();
+            // Short-circuit candidates that can't possibly be within the threshold.
+            if candidate_len.abs_diff(input_len) > threshold {
+                return None;
+            }
+            let distance = levenshtein(input, candidate);
+            if distance <= threshold {
+                Some((distance, candidate.as_str()))
+            } else {
+                None
+            }
+        })
+        .collect();
+    scored.sort();
+    scored.dedup_by(|a, b| a.1 == b.1);
+    scored
+        .into_iter()
+        .take(max_suggestions)
+        .map(|(_, name)| name.to_owned())
+        .collect()
+}
+
 impl GoalInfo {
     pub fn new<'a, I: IntoIterator<Item = &'a str>>(
         scope_name: &str,
@@ -53,11 +367,121 @@ impl GoalInfo {
         is_auxiliary: bool,
         aliases: I,
     ) -> Self {
+        // Intern the goal's scope name (and aliases) up front so their symbols get stable ids at
+        // registration time, before any command-line parsing interns them on the hot path.
+        intern_scope(scope_name);
+        let aliases: Vec<String> = aliases.into_iter().map(str::to_owned).collect();
+        for alias in &aliases {
+            intern_scope(alias);
+        }
         Self {
             scope_name: scope_name.to_owned(),
             is_builtin,
             is_auxiliary,
-            aliases: aliases.into_iter().map(str::to_owned).collect(),
+            aliases,
         }
     }
+
+    /// The interned symbol for this goal's scope name.
+    pub fn scope_symbol(&self) -> ScopeSymbol {
+        intern_scope(&self.scope_name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn canonicalize_treats_dash_and_underscore_as_the_same_separator() {
+        assert_eq!(canonicalize("my-tool"), canonicalize("my_tool"));
+        assert_eq!(canonicalize("my__tool"), canonicalize("my-tool"));
+        assert_eq!(canonicalize("my-_tool"), canonicalize("my-tool"));
+    }
+
+    #[test]
+    fn named_scopes_with_different_separators_compare_equal() {
+        assert_eq!(Scope::named("my-tool"), Scope::named("my_tool"));
+        assert_ne!(Scope::named("my-tool"), Scope::named("other-tool"));
+    }
+
+    #[test]
+    fn named_scopes_preserve_first_seen_display_spelling() {
+        let unique = format!("disp-spelling-{}", std::process::id());
+        let first = Scope::named(&unique);
+        let second = Scope::named(&unique.replace('-', "_"));
+        assert_eq!(first.name(), unique);
+        assert_eq!(second.name(), unique);
+    }
+
+    #[test]
+    fn is_already_canonical_matches_canonicalize() {
+        assert!(is_already_canonical("my-tool"));
+        assert!(is_already_canonical("a"));
+        assert!(!is_already_canonical("my_tool"));
+        assert!(!is_already_canonical("my--tool"));
+        for name in ["my-tool", "a", "my_tool", "my--tool", "my-_tool"] {
+            assert_eq!(is_already_canonical(name), canonicalize(name) == name);
+        }
+    }
+
+    #[test]
+    fn scope_ordering_is_by_canonical_name_not_intern_order() {
+        // Intern "zzz" before "aaa" so an id-ordered `Ord` would put them in the wrong order.
+        let zzz = Scope::named("zzz-order-probe");
+        let aaa = Scope::named("aaa-order-probe");
+        assert!(aaa < zzz);
+    }
+
+    #[test]
+    fn validate_scope_name_reports_specific_reasons() {
+        assert_eq!(validate_scope_name(""), Err(ScopeNameError::Empty));
+        assert_eq!(
+            validate_scope_name("pants"),
+            Err(ScopeNameError::ReservedWord("pants".to_owned()))
+        );
+        assert_eq!(
+            validate_scope_name("-foo"),
+            Err(ScopeNameError::InvalidLeadingChar)
+        );
+        assert_eq!(
+            validate_scope_name("foo-"),
+            Err(ScopeNameError::TrailingSeparator)
+        );
+        assert_eq!(
+            validate_scope_name("foo--bar"),
+            Err(ScopeNameError::ConsecutiveSeparators)
+        );
+        assert_eq!(
+            validate_scope_name("foo.bar"),
+            Err(ScopeNameError::InvalidChar { pos: 3, ch: '.' })
+        );
+        assert!(validate_scope_name("foo-bar_baz").is_ok());
+    }
+
+    #[test]
+    fn levenshtein_basic_distances() {
+        assert_eq!(levenshtein("", ""), 0);
+        assert_eq!(levenshtein("test", "test"), 0);
+        assert_eq!(levenshtein("test", "tests"), 1);
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn suggest_scopes_finds_closest_goal() {
+        let goals = vec![GoalInfo::new("test", true, false, vec!["tst"])];
+        let suggestions = suggest_scopes("tesst", &goals, &[], 3);
+        assert_eq!(suggestions, vec!["test".to_string()]);
+    }
+
+    #[test]
+    fn suggest_scopes_respects_max_suggestions() {
+        let goals = vec![
+            GoalInfo::new("fmta", false, false, Vec::<&str>::new()),
+            GoalInfo::new("fmtb", false, false, Vec::<&str>::new()),
+            GoalInfo::new("fmtc", false, false, Vec::<&str>::new()),
+        ];
+        let suggestions = suggest_scopes("fmt", &goals, &[], 2);
+        assert_eq!(suggestions.len(), 2);
+    }
 }
@@ -219,6 +219,177 @@ pub fn lift_digest(digest: &Value) -> Result<hashing::Digest, String> {
   ))
 }
 
+///
+/// An implementation of the GNU make jobserver protocol, used to share a global parallelism
+/// budget with subprocesses (compilers, `make`, `ninja`, `cargo`) that do their own internal
+/// parallelization, so they don't oversubscribe the machine.
+///
+/// On Unix a token pool of `N` is an anonymous pipe pre-loaded with `N-1` bytes plus the one
+/// implicit token the top process owns. Acquiring a token reads one byte (or takes the implicit
+/// token); releasing writes it back. On platforms without pipe support the jobserver is inert and
+/// no `MAKEFLAGS` is exported.
+///
+#[derive(Clone)]
+pub struct Jobserver {
+  inner: Option<Arc<JobserverInner>>,
+}
+
+#[cfg(unix)]
+struct JobserverInner {
+  read_fd: std::os::unix::io::RawFd,
+  write_fd: std::os::unix::io::RawFd,
+  // The total number of tokens in the pool, for `MAKEFLAGS`'s `-jN`.
+  tokens: usize,
+  // The single implicit token owned by the top process; handed out without touching the pipe.
+  implicit_available: std::sync::atomic::AtomicBool,
+}
+
+/// A held jobserver token, returned to the pool on drop (including on panic or cancellation).
+pub struct JobserverToken {
+  inner: Option<Arc<JobserverInner>>,
+  was_implicit: bool,
+}
+
+#[cfg(unix)]
+impl Jobserver {
+  pub fn new(tokens: usize) -> std::io::Result<Jobserver> {
+    use std::os::unix::io::RawFd;
+    let mut fds: [RawFd; 2] = [0; 2];
+    if unsafe { libc::pipe(fds.as_mut_ptr()) } != 0 {
+      return Err(std::io::Error::last_os_error());
+    }
+    // Pre-load the pipe with N-1 tokens; the Nth is the implicit token. Writing them all
+    // synchronously here would block (deadlocking startup) once N-1 exceeds the pipe's kernel
+    // buffer (~64KiB on Linux), since nothing is reading yet. Fill it from a background thread
+    // instead: the writes there block harmlessly until `acquire` drains tokens to make room.
+    let write_fd = fds[1];
+    let to_write = tokens.saturating_sub(1);
+    std::thread::spawn(move || {
+      let byte = [b'+'];
+      for _ in 0..to_write {
+        if unsafe { libc::write(write_fd, byte.as_ptr() as *const libc::c_void, 1) } != 1 {
+          return;
+        }
+      }
+    });
+    Ok(Jobserver {
+      inner: Some(Arc::new(JobserverInner {
+        read_fd: fds[0],
+        write_fd: fds[1],
+        tokens,
+        implicit_available: std::sync::atomic::AtomicBool::new(true),
+      })),
+    })
+  }
+
+  /// Acquire a token, blocking (cooperatively) until one is available.
+  pub async fn acquire(&self) -> JobserverToken {
+    use std::sync::atomic::Ordering;
+    let inner = match &self.inner {
+      Some(inner) => inner.clone(),
+      None => {
+        return JobserverToken {
+          inner: None,
+          was_implicit: false,
+        }
+      }
+    };
+    // Prefer the implicit token so we never block while holding it.
+    if inner.implicit_available.swap(false, Ordering::SeqCst) {
+      return JobserverToken {
+        inner: Some(inner),
+        was_implicit: true,
+      };
+    }
+    let read_fd = inner.read_fd;
+    // The pipe read blocks, so perform it off the async executor. A token has only actually been
+    // acquired once `read` reports that it consumed the one byte we asked for: retry on EINTR,
+    // and keep retrying on transient errors rather than handing out a token we never actually
+    // read, which would over-grant concurrency and desync the shared token count. If no byte is
+    // read in the end (EOF, or the retry bound below), the returned token is inert: nothing is
+    // written back to the pipe when it is dropped.
+    const MAX_ACQUIRE_ATTEMPTS: u32 = 100;
+    let acquired = tokio::task::spawn_blocking(move || {
+      let mut byte = [0u8; 1];
+      let mut attempts = 0u32;
+      loop {
+        let res = unsafe { libc::read(read_fd, byte.as_mut_ptr() as *mut libc::c_void, 1) };
+        if res == 1 {
+          return true;
+        }
+        if res < 0 && std::io::Error::last_os_error().kind() == std::io::ErrorKind::Interrupted {
+          continue;
+        }
+        if res == 0 {
+          // EOF: the write end has closed, which only happens once the jobserver itself has been
+          // torn down. No byte will ever arrive, so give up immediately instead of retrying
+          // forever and leaking this thread.
+          return false;
+        }
+        // An unexpected error: back off briefly and retry, but only up to a bound so a
+        // persistently broken fd can't leak this thread forever either.
+        attempts += 1;
+        if attempts >= MAX_ACQUIRE_ATTEMPTS {
+          return false;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+      }
+    })
+    .await
+    .unwrap_or(false);
+    JobserverToken {
+      inner: if acquired { Some(inner) } else { None },
+      was_implicit: false,
+    }
+  }
+
+  /// The `MAKEFLAGS` value to export to subprocesses, or `None` when the jobserver is inert.
+  pub fn makeflags(&self) -> Option<String> {
+    self.inner.as_ref().map(|inner| {
+      format!(
+        "--jobserver-auth={},{} -j{}",
+        inner.read_fd, inner.write_fd, inner.tokens
+      )
+    })
+  }
+}
+
+#[cfg(not(unix))]
+impl Jobserver {
+  pub fn new(_tokens: usize) -> std::io::Result<Jobserver> {
+    // No pipe support: the jobserver is inert.
+    Ok(Jobserver { inner: None })
+  }
+
+  pub async fn acquire(&self) -> JobserverToken {
+    JobserverToken {
+      inner: None,
+      was_implicit: false,
+    }
+  }
+
+  pub fn makeflags(&self) -> Option<String> {
+    None
+  }
+}
+
+impl Drop for JobserverToken {
+  fn drop(&mut self) {
+    #[cfg(unix)]
+    {
+      use std::sync::atomic::Ordering;
+      if let Some(inner) = &self.inner {
+        if self.was_implicit {
+          inner.implicit_available.store(true, Ordering::SeqCst);
+        } else {
+          let byte = [b'+'];
+          unsafe { libc::write(inner.write_fd, byte.as_ptr() as *const libc::c_void, 1) };
+        }
+      }
+    }
+  }
+}
+
 /// A Node that represents a set of processes to execute on specific platforms.
 ///
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
@@ -336,6 +507,160 @@ impl MultiPlatformExecuteProcess {
   }
 }
 
+///
+/// Which backend handles a process once the `CommandRunner` has determined it's compatible with
+/// local execution: the default temp-directory runner, or the namespace-isolated sandbox below.
+///
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum LocalExecutionStrategy {
+  /// Run via the `CommandRunner` passed to `Core` (a scratch temp dir; the existing behavior).
+  Direct,
+  /// Run inside fresh Linux namespaces; see `NamespaceSandbox`.
+  NamespaceSandbox,
+}
+
+///
+/// A local-execution backend that runs a process inside fresh Linux user + mount + PID + network
+/// namespaces, so it is isolated from the host filesystem and network and cannot accidentally
+/// depend on non-hermetic state. Selectable alongside the existing temp-directory local runner,
+/// and gated to Linux: on other kernels `run` returns a clean unsupported error.
+///
+pub struct NamespaceSandbox;
+
+#[cfg(target_os = "linux")]
+impl NamespaceSandbox {
+  ///
+  /// Materialize the input digest into a read-only root, run the process in isolated namespaces
+  /// with a writable tmpfs overlay, and snapshot the declared outputs back into the store.
+  ///
+  pub async fn run(
+    core: Arc<Core>,
+    req: Process,
+  ) -> Result<process_execution::FallibleProcessResultWithPlatform, String> {
+    use std::os::unix::process::CommandExt;
+
+    // Materialize the inputs into a directory we will bind-mount read-only into the sandbox.
+    let inputs = tempfile::TempDir::new()
+      .map_err(|e| format!("Failed to create sandbox inputs dir: {}", e))?;
+    core
+      .store()
+      .materialize_directory(inputs.path().to_owned(), req.input_files)
+      .await?;
+
+    // A writable tmpfs overlay for declared outputs.
+    let outputs = tempfile::TempDir::new()
+      .map_err(|e| format!("Failed to create sandbox outputs dir: {}", e))?;
+
+    let (uid, gid) = (
+      unsafe { libc::getuid() },
+      unsafe { libc::getgid() },
+    );
+    let input_root = inputs.path().to_owned();
+    let output_root = outputs.path().to_owned();
+
+    let mut command = std::process::Command::new(&req.argv[0]);
+    command
+      .args(&req.argv[1..])
+      .env_clear()
+      .envs(&req.env)
+      .current_dir(&output_root);
+
+    unsafe {
+      command.pre_exec(move || {
+        use nix::mount::{mount, MsFlags};
+        use nix::sched::{unshare, CloneFlags};
+
+        // New user, mount, PID and network namespaces. Dropping the outer network namespace means
+        // no accidental network access unless explicitly granted.
+        unshare(
+          CloneFlags::CLONE_NEWUSER
+            | CloneFlags::CLONE_NEWNS
+            | CloneFlags::CLONE_NEWPID
+            | CloneFlags::CLONE_NEWNET,
+        )
+        .map_err(std::io::Error::from)?;
+
+        // Map the invoking uid/gid into the new user namespace.
+        std::fs::write("/proc/self/setgroups", b"deny")?;
+        std::fs::write("/proc/self/uid_map", format!("0 {} 1", uid))?;
+        std::fs::write("/proc/self/gid_map", format!("0 {} 1", gid))?;
+
+        // Bind the materialized inputs read-only, and a writable tmpfs for outputs.
+        mount(
+          Some(input_root.as_path()),
+          input_root.as_path(),
+          None::<&str>,
+          MsFlags::MS_BIND | MsFlags::MS_RDONLY | MsFlags::MS_REC,
+          None::<&str>,
+        )
+        .map_err(std::io::Error::from)?;
+        // MS_RDONLY is ignored when a bind mount is created, so remount the bind read-only.
+        mount(
+          None::<&str>,
+          input_root.as_path(),
+          None::<&str>,
+          MsFlags::MS_REMOUNT | MsFlags::MS_BIND | MsFlags::MS_RDONLY,
+          None::<&str>,
+        )
+        .map_err(std::io::Error::from)?;
+        mount(
+          Some("tmpfs"),
+          output_root.as_path(),
+          Some("tmpfs"),
+          MsFlags::empty(),
+          None::<&str>,
+        )
+        .map_err(std::io::Error::from)?;
+        Ok(())
+      });
+    }
+
+    let output = tokio::task::spawn_blocking(move || command.output())
+      .await
+      .map_err(|e| format!("Sandbox task panicked: {}", e))?
+      .map_err(|e| format!("Failed to spawn sandboxed process: {}", e))?;
+
+    // Snapshot only the declared output files/dirs from the writable layer.
+    let mut output_paths = req.output_files.clone();
+    output_paths.extend(req.output_directories.iter().cloned());
+    let path_globs = PathGlobs::new(
+      output_paths
+        .iter()
+        .map(|p| p.to_string_lossy().into_owned())
+        .collect(),
+      StrictGlobMatching::Ignore,
+      GlobExpansionConjunction::AllMatch,
+    )
+    .parse()?;
+    let snapshot = store::Snapshot::capture_snapshot_from_arbitrary_root(
+      core.store(),
+      core.executor.clone(),
+      output_root,
+      path_globs,
+      None,
+    )
+    .await?;
+
+    Ok(process_execution::FallibleProcessResultWithPlatform::new(
+      bytes::Bytes::from(output.stdout),
+      bytes::Bytes::from(output.stderr),
+      output.status.code().unwrap_or(-1),
+      snapshot.digest,
+      req.target_platform.into(),
+    ))
+  }
+}
+
+#[cfg(not(target_os = "linux"))]
+impl NamespaceSandbox {
+  pub async fn run(
+    _core: Arc<Core>,
+    _req: Process,
+  ) -> Result<process_execution::FallibleProcessResultWithPlatform, String> {
+    Err("The namespace sandbox execution strategy is only supported on Linux.".to_owned())
+  }
+}
+
 impl From<MultiPlatformExecuteProcess> for NodeKey {
   fn from(n: MultiPlatformExecuteProcess) -> Self {
     NodeKey::MultiPlatformExecuteProcess(Box::new(n))
@@ -347,24 +672,72 @@ impl WrappedNode for MultiPlatformExecuteProcess {
 
   fn run(self, context: Context) -> NodeFuture<ProcessResult> {
     let request = self.0;
-    let execution_context = process_execution::Context::new(
+    // A cancellation token, tripped when the owning session is aborted. It is threaded into the
+    // execution Context so the command runner can propagate a kill to the underlying process, and
+    // is also observed here so the node resolves promptly rather than after the process exits.
+    // The execution Context (this token and the MAKEFLAGS below) is passed only to the `Direct`
+    // strategy's command runner: the `NamespaceSandbox` strategy runs without MAKEFLAGS, and its
+    // process is not killed on cancellation, although the node still resolves as cancelled.
+    let cancellation_token = context.session.cancellation_token();
+    let mut execution_context = process_execution::Context::new(
       context.session.workunit_store(),
       context.session.build_id().to_string(),
+      cancellation_token.clone(),
     );
-    if context
+    // Export MAKEFLAGS so cooperating subprocesses draw from the same parallelism budget, and
+    // inherit the jobserver fds rather than closing them on exec.
+    execution_context.makeflags = context.core.jobserver.makeflags();
+    if let Some(compatible_request) = context
       .core
       .command_runner
       .extract_compatible_request(&request)
-      .is_some()
     {
       Box::pin(async move {
-        let res = context
-          .core
-          .command_runner
-          .run(request, execution_context)
-          .await
-          .map_err(|e| throw(&format!("Failed to execute process: {}", e)))?;
+        // Draw a token from the shared jobserver pool so the engine's own execute-process
+        // concurrency is bounded by the same budget exposed to subprocesses. Released on drop,
+        // including on panic or cancellation.
+        let _jobserver_token = context.core.jobserver.acquire().await;
+
+        let started = std::time::Instant::now();
+        // The span of the node whose workunit the progress updates below should attach to.
+        let span_id = workunit_store::get_parent_id();
+
+        let run: std::pin::Pin<
+          Box<dyn std::future::Future<Output = Result<process_execution::FallibleProcessResultWithPlatform, String>> + Send>,
+        > = match context.core.local_execution_strategy {
+          LocalExecutionStrategy::NamespaceSandbox => {
+            Box::pin(NamespaceSandbox::run(context.core.clone(), compatible_request))
+          }
+          LocalExecutionStrategy::Direct => {
+            Box::pin(context.core.command_runner.run(request, execution_context))
+          }
+        };
+        futures::pin_mut!(run);
+
+        // Emit incremental progress (elapsed time) while the process runs, and resolve as a
+        // cancellation as soon as the session token is tripped.
+        let mut ticker = tokio::time::interval(Duration::from_millis(500));
+        let res = loop {
+          tokio::select! {
+            res = &mut run => break res,
+            _ = cancellation_token.cancelled() => {
+              // The `Direct` runner has been asked (via the token) to kill the process; a sandboxed
+              // process is left to run to completion. Either way, surface a distinct cancellation
+              // rather than conflating this with graph invalidation, so callers (and retry
+              // logic) can tell a user-initiated abort from an invalidated dependency.
+              return Err(Failure::Cancelled);
+            }
+            _ = ticker.tick() => {
+              workunit_store::update_workunit(
+                span_id.clone(),
+                None,
+                format!("running for {:.1}s", started.elapsed().as_secs_f64()),
+              );
+            }
+          }
+        };
 
+        let res = res.map_err(|e| throw(&format!("Failed to execute process: {}", e)))?;
         Ok(ProcessResult(res))
       })
       .compat()
@@ -421,6 +794,72 @@ impl From<ReadLink> for NodeKey {
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct DigestFile(pub File);
 
+///
+/// A FastCDC-style Gear-hash table, seeded deterministically so chunk boundaries are stable
+/// across processes and machines.
+///
+fn gear_table() -> [u64; 256] {
+  let mut table = [0u64; 256];
+  // xorshift64, purely to spread the byte values across the 64-bit fingerprint space.
+  let mut state = 0x2545_F491_4F6C_DD1Du64;
+  for entry in table.iter_mut() {
+    state ^= state << 13;
+    state ^= state >> 7;
+    state ^= state << 17;
+    *entry = state;
+  }
+  table
+}
+
+///
+/// Find the next content-defined chunk boundary in `data`, returning its length. Uses a rolling
+/// Gear-hash fingerprint with "normalized chunking": a stricter mask (more 1-bits, so boundaries
+/// are rarer) while below the target average size, and a looser mask once past it. `min` bytes are
+/// skipped before any boundary is considered, and `max` is a hard cutoff.
+///
+fn next_chunk_len(data: &[u8], gear: &[u64; 256], min: usize, avg: usize, max: usize) -> usize {
+  let len = data.len();
+  if len <= min {
+    return len;
+  }
+  let len = len.min(max);
+  let bits = (avg as f64).log2().round() as u32;
+  let mask_s = (1u64 << (bits + 2)) - 1;
+  let mask_l = (1u64 << bits.saturating_sub(2)) - 1;
+  let center = avg.min(len);
+
+  let mut fp = 0u64;
+  let mut i = min;
+  while i < center {
+    fp = (fp << 1).wrapping_add(gear[data[i] as usize]);
+    if fp & mask_s == 0 {
+      return i;
+    }
+    i += 1;
+  }
+  while i < len {
+    fp = (fp << 1).wrapping_add(gear[data[i] as usize]);
+    if fp & mask_l == 0 {
+      return i;
+    }
+    i += 1;
+  }
+  len
+}
+
+///
+/// The backoff for a retried download attempt: exponential with a cap, plus equal jitter, so that
+/// many concurrent retries don't all hammer the remote at once.
+///
+fn backoff(base: Duration, attempt: u32) -> Duration {
+  let capped = (base.as_millis() as u64)
+    .saturating_mul(1u64 << attempt.min(5))
+    .min(5_000);
+  let half = capped / 2;
+  let jitter = (rand::random::<f64>() * half as f64) as u64;
+  Duration::from_millis(half + jitter)
+}
+
 impl WrappedNode for DigestFile {
   type Item = hashing::Digest;
 
@@ -432,12 +871,40 @@ impl WrappedNode for DigestFile {
         .read_file(&self.0)
         .map_err(|e| throw(&format!("{}", e)))
         .await?;
-      context
-        .core
-        .store()
-        .store_file_bytes(content.content, true)
-        .map_err(|e| throw(&e))
-        .await
+      let store = context.core.store();
+
+      // Large files are split into content-defined chunks so that near-identical files share most
+      // of their chunk blobs in the store; small files keep the cheaper whole-file path. Gated on
+      // a store config flag so existing digests remain stable when disabled.
+      if store.chunk_large_files && content.content.len() >= store.chunk_min_file_size {
+        let data = content.content;
+        let gear = gear_table();
+        let (min, avg, max) = store.chunk_sizes;
+        let mut chunks = Vec::new();
+        let mut offset = 0;
+        while offset < data.len() {
+          let chunk_len = next_chunk_len(&data[offset..], &gear, min, avg, max);
+          let chunk = data.slice(offset..offset + chunk_len);
+          let chunk_digest = store
+            .store_file_bytes(chunk, true)
+            .map_err(|e| throw(&e))
+            .await?;
+          chunks.push(chunk_digest);
+          offset += chunk_len;
+        }
+        // The overall file digest matches the whole-file digest, so callers are unaffected; the
+        // chunk list is recorded as a manifest for reconstruction by load_file_bytes_with.
+        let file_digest = hashing::Digest::of_bytes(&data);
+        store
+          .store_file_chunks(file_digest, chunks)
+          .map_err(|e| throw(&e))
+          .await
+      } else {
+        store
+          .store_file_bytes(content.content, true)
+          .map_err(|e| throw(&e))
+          .await
+      }
     })
     .compat()
     .to_boxed()
@@ -490,14 +957,18 @@ pub struct Snapshot(pub Key);
 impl Snapshot {
   fn create(context: Context, path_globs: PreparedPathGlobs) -> NodeFuture<store::Snapshot> {
     // Recursively expand PathGlobs into PathStats.
-    // We rely on Context::expand tracking dependencies for scandirs,
+    // We rely on Context::expand (or the parallel walker) tracking dependencies for scandirs,
     // and store::Snapshot::from_path_stats tracking dependencies for file digests.
 
     Box::pin(async move {
-      let path_stats = context
-        .expand(path_globs)
-        .map_err(|e| throw(&format!("{}", e)))
-        .await?;
+      let path_stats = if context.core.walk_parallelism > 1 {
+        Self::expand_globs_parallel(&context, path_globs).await?
+      } else {
+        context
+          .expand(path_globs)
+          .map_err(|e| throw(&format!("{}", e)))
+          .await?
+      };
       store::Snapshot::from_path_stats(context.core.store(), context.clone(), path_stats)
         .map_err(|e| throw(&format!("Snapshot failed: {}", e)))
         .await
@@ -506,6 +977,104 @@ impl Snapshot {
     .to_boxed()
   }
 
+  ///
+  /// A throughput-oriented alternative to `Context::expand` for large source trees: fan out
+  /// directory traversal across a bounded worker pool, deduplicating in-flight directory visits
+  /// and collecting matched `PathStat`s as they are discovered. Each `scandir`/`read_link` still
+  /// flows through the graph (via `Context`'s `VFS` impl) so invalidation remains correct.
+  /// Ignored entries, and any subdirectory that no glob in `path_globs` could possibly match
+  /// underneath, are pruned before descent.
+  ///
+  async fn expand_globs_parallel(
+    context: &Context,
+    path_globs: PreparedPathGlobs,
+  ) -> Result<Vec<PathStat>, Failure> {
+    use futures::stream::FuturesUnordered;
+
+    let semaphore = Arc::new(tokio::sync::Semaphore::new(context.core.walk_parallelism));
+    let visited = Arc::new(std::sync::Mutex::new(
+      std::collections::HashSet::<PathBuf>::new(),
+    ));
+    let path_globs = Arc::new(path_globs);
+
+    // Scan a single directory, returning the matched PathStats and the subdirectories to descend.
+    async fn scan(
+      context: Context,
+      semaphore: Arc<tokio::sync::Semaphore>,
+      path_globs: Arc<PreparedPathGlobs>,
+      dir: Dir,
+    ) -> Result<(Vec<PathStat>, Vec<Dir>), Failure> {
+      let _permit = semaphore.acquire().await;
+      let listing = context.scandir(dir).await?;
+      let mut matched = Vec::new();
+      let mut subdirs = Vec::new();
+      for stat in listing.0.iter() {
+        // Prune ignored paths early so we never descend into them.
+        if context.is_ignored(stat) {
+          continue;
+        }
+        match stat {
+          fs::Stat::File(file) => {
+            if path_globs.matches(&file.path) {
+              matched.push(PathStat::file(file.path.clone(), file.clone()));
+            }
+          }
+          fs::Stat::Dir(subdir) => {
+            if path_globs.matches(&subdir.path) {
+              matched.push(PathStat::dir(subdir.path.clone(), subdir.clone()));
+            }
+            // Only descend if some glob could still match something under this subdir: without
+            // this, every directory in the tree is scanned regardless of the globs, which both
+            // regresses throughput and invalidates on changes outside the requested globs.
+            if path_globs.could_match_descendants(&subdir.path) {
+              subdirs.push(Dir(subdir.path.clone()));
+            }
+          }
+          fs::Stat::Link(link) => {
+            // Resolve symlinks through the graph, then re-descend from the destination.
+            if path_globs.could_match_descendants(&link.path) {
+              let dest = context.read_link(link).await?;
+              if dest.is_dir() {
+                subdirs.push(Dir(dest));
+              }
+            }
+          }
+        }
+      }
+      Ok((matched, subdirs))
+    }
+
+    let mut results = Vec::new();
+    let mut inflight = FuturesUnordered::new();
+    for root in path_globs.roots() {
+      if visited.lock().unwrap().insert(root.0.clone()) {
+        inflight.push(scan(
+          context.clone(),
+          semaphore.clone(),
+          path_globs.clone(),
+          root,
+        ));
+      }
+    }
+
+    while let Some(result) = inflight.next().await {
+      let (matched, subdirs) = result?;
+      results.extend(matched);
+      for subdir in subdirs {
+        if visited.lock().unwrap().insert(subdir.0.clone()) {
+          inflight.push(scan(
+            context.clone(),
+            semaphore.clone(),
+            path_globs.clone(),
+            subdir,
+          ));
+        }
+      }
+    }
+
+    Ok(results)
+  }
+
   pub fn lift_path_globs(item: &Value) -> Result<PreparedPathGlobs, String> {
     let globs = externs::project_multi_strs(item, "globs");
 
@@ -610,6 +1179,190 @@ impl From<Snapshot> for NodeKey {
   }
 }
 
+///
+/// The archive formats that a DownloadedFile can transparently unpack into a Snapshot of its
+/// contents, rather than storing the downloaded blob as a single file.
+///
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ArchiveFormat {
+  Tar,
+  TarGz,
+  TarZst,
+  Zip,
+}
+
+impl ArchiveFormat {
+  ///
+  /// Parse the `archive_format` field lifted from the Python value. An empty string means the
+  /// download should not be extracted.
+  ///
+  fn lift(value: &str) -> Result<Option<ArchiveFormat>, String> {
+    match value {
+      "" => Ok(None),
+      "tar" => Ok(Some(ArchiveFormat::Tar)),
+      "tar.gz" => Ok(Some(ArchiveFormat::TarGz)),
+      "tar.zst" => Ok(Some(ArchiveFormat::TarZst)),
+      "zip" => Ok(Some(ArchiveFormat::Zip)),
+      other => Err(format!("Unrecognized archive_format: {}", other)),
+    }
+  }
+}
+
+///
+/// A compression codec that the download stream can be decompressed through before hashing, so
+/// that the stored (and digest-verified) bytes are the decompressed form.
+///
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Codec {
+  Identity,
+  Gzip,
+  Bzip2,
+  Xz,
+  Zstd,
+}
+
+impl Codec {
+  ///
+  /// Choose a codec from an explicitly declared name, falling back to sniffing the URL suffix and
+  /// then the `Content-Encoding` response header. An empty declared name with no sniffable hint
+  /// means "store the compressed bytes unchanged".
+  ///
+  fn lift(declared: &str, url: &Url, content_encoding: Option<&str>) -> Result<Codec, String> {
+    let from_name = |name: &str| match name {
+      "" | "identity" => Some(Codec::Identity),
+      "gzip" | "gz" => Some(Codec::Gzip),
+      "bzip2" | "bz2" => Some(Codec::Bzip2),
+      "xz" => Some(Codec::Xz),
+      "zstd" | "zst" => Some(Codec::Zstd),
+      _ => None,
+    };
+    if !declared.is_empty() {
+      return from_name(declared).ok_or_else(|| format!("Unrecognized codec: {}", declared));
+    }
+    let sniffed = url
+      .path()
+      .rsplit('.')
+      .next()
+      .and_then(from_name)
+      .or_else(|| content_encoding.and_then(from_name));
+    Ok(sniffed.unwrap_or(Codec::Identity))
+  }
+}
+
+///
+/// A source of opaque bytes addressed by a URL. Implementations exist per scheme (HTTP(S), S3,
+/// GCS, local files); all yield an async byte stream that plugs into the shared
+/// SizeLimiter/WriterHasher/digest-verification pipeline, so there is a single content-addressed
+/// ingestion path regardless of where the bytes come from.
+///
+#[async_trait]
+trait BlobSource: Send + Sync {
+  async fn stream(
+    &self,
+  ) -> Result<futures::stream::BoxStream<'static, Result<bytes::Bytes, String>>, String>;
+}
+
+/// Fetch via the shared `Core` HTTP client.
+struct HttpBlobSource {
+  core: Arc<Core>,
+  url: Url,
+}
+
+#[async_trait]
+impl BlobSource for HttpBlobSource {
+  async fn stream(
+    &self,
+  ) -> Result<futures::stream::BoxStream<'static, Result<bytes::Bytes, String>>, String> {
+    let response = self
+      .core
+      .http_client
+      .get(self.url.clone())
+      .send()
+      .await
+      .map_err(|err| format!("Error downloading file: {}", err))?;
+    if !response.status().is_success() {
+      return Err(format!(
+        "HTTP error ({}) downloading {}",
+        response.status().as_str(),
+        self.url
+      ));
+    }
+    Ok(
+      response
+        .bytes_stream()
+        .map_err(|err| format!("Error reading URL fetch response: {}", err))
+        .boxed(),
+    )
+  }
+}
+
+/// Read from a local `file://` path.
+struct FileBlobSource {
+  path: PathBuf,
+}
+
+#[async_trait]
+impl BlobSource for FileBlobSource {
+  async fn stream(
+    &self,
+  ) -> Result<futures::stream::BoxStream<'static, Result<bytes::Bytes, String>>, String> {
+    let file = tokio::fs::File::open(&self.path)
+      .await
+      .map_err(|err| format!("Error opening {}: {}", self.path.display(), err))?;
+    Ok(
+      tokio_util::io::ReaderStream::new(file)
+        .map_ok(bytes::Bytes::from)
+        .map_err(|err| format!("Error reading file: {}", err))
+        .boxed(),
+    )
+  }
+}
+
+/// Read from an object-store backend (`s3://`, `gs://`).
+struct ObjectStoreBlobSource {
+  url: Url,
+}
+
+#[async_trait]
+impl BlobSource for ObjectStoreBlobSource {
+  async fn stream(
+    &self,
+  ) -> Result<futures::stream::BoxStream<'static, Result<bytes::Bytes, String>>, String> {
+    let (store, path) = object_store::parse_url(&self.url)
+      .map_err(|err| format!("Error resolving object store for {}: {}", self.url, err))?;
+    let result = store
+      .get(&path)
+      .await
+      .map_err(|err| format!("Error fetching {}: {}", self.url, err))?;
+    Ok(
+      result
+        .into_stream()
+        .map_err(|err| format!("Error reading {}: {}", self.url, err))
+        .boxed(),
+    )
+  }
+}
+
+///
+/// Dispatch on the URL scheme to the appropriate blob source.
+///
+fn blob_source_for(url: &Url, core: &Arc<Core>) -> Result<Box<dyn BlobSource>, String> {
+  match url.scheme() {
+    "http" | "https" => Ok(Box::new(HttpBlobSource {
+      core: core.clone(),
+      url: url.clone(),
+    })),
+    "file" => {
+      let path = url
+        .to_file_path()
+        .map_err(|_| format!("Invalid file URL: {}", url))?;
+      Ok(Box::new(FileBlobSource { path }))
+    }
+    "s3" | "gs" => Ok(Box::new(ObjectStoreBlobSource { url: url.clone() })),
+    other => Err(format!("Unsupported URL scheme for DownloadedFile: {}", other)),
+  }
+}
+
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct DownloadedFile(pub Key);
 
@@ -619,6 +1372,8 @@ impl DownloadedFile {
     core: Arc<Core>,
     url: Url,
     digest: hashing::Digest,
+    archive_format: Option<ArchiveFormat>,
+    codec: Codec,
   ) -> BoxFuture<store::Snapshot, String> {
     let file_name = try_future!(url
       .path_segments()
@@ -629,116 +1384,335 @@ impl DownloadedFile {
     Box::pin(async move {
       let maybe_bytes = core.store().load_file_bytes_with(digest, |_| ()).await?;
       if maybe_bytes.is_none() {
-        DownloadedFile::download(core.clone(), url, file_name.clone(), digest)
-          .compat()
-          .await?;
+        // HTTP(S) uses the retrying, range-resuming downloader; other schemes resolve through a
+        // blob source but share the same content-addressed ingestion pipeline.
+        match url.scheme() {
+          "http" | "https" => {
+            DownloadedFile::download(core.clone(), url, file_name.clone(), digest, codec)
+              .compat()
+              .await?;
+          }
+          _ => {
+            let source = blob_source_for(&url, &core)?;
+            DownloadedFile::download_via_source(core.clone(), source, digest, codec).await?;
+          }
+        }
+      }
+      match archive_format {
+        // Unpack the (now digest-verified) blob into a Snapshot of its contents.
+        Some(format) => Self::snapshot_of_archive(core, digest, format).await,
+        // Store the blob as a single file, as before.
+        None => {
+          core
+            .store()
+            .snapshot_of_one_file(PathBuf::from(file_name), digest, true)
+            .await
+        }
       }
-      core
-        .store()
-        .snapshot_of_one_file(PathBuf::from(file_name), digest, true)
-        .await
     })
     .compat()
     .to_boxed()
   }
 
+  ///
+  /// Unpack the already-stored blob with the given digest into a Snapshot of its contents. The
+  /// archive is extracted into a transient directory, preserving relative paths and executable
+  /// bits, and then captured back into the content-addressed store.
+  ///
+  async fn snapshot_of_archive(
+    core: Arc<Core>,
+    digest: hashing::Digest,
+    format: ArchiveFormat,
+  ) -> Result<store::Snapshot, String> {
+    let bytes = core
+      .store()
+      .load_file_bytes_with(digest, |bytes| bytes::Bytes::copy_from_slice(bytes))
+      .await?
+      .ok_or_else(|| format!("Downloaded bytes for {:?} were not in the store", digest))?;
+
+    let dest = tempfile::TempDir::new()
+      .map_err(|e| format!("Failed to create a temporary directory for extraction: {}", e))?;
+    Self::extract(format, &bytes, dest.path())?;
+
+    let path_globs = PathGlobs::new(
+      vec!["**".to_owned()],
+      StrictGlobMatching::Ignore,
+      GlobExpansionConjunction::AllMatch,
+    )
+    .parse()?;
+    store::Snapshot::capture_snapshot_from_arbitrary_root(
+      core.store(),
+      core.executor.clone(),
+      dest.path().to_owned(),
+      path_globs,
+      None,
+    )
+    .await
+  }
+
+  ///
+  /// Extract the archive bytes into `dest`, streaming each entry directly from the reader. tar
+  /// entries preserve their recorded mode bits; zip entries preserve the external-attributes mode.
+  ///
+  fn extract(format: ArchiveFormat, bytes: &[u8], dest: &Path) -> Result<(), String> {
+    let map_io = |e: std::io::Error| format!("Failed to extract archive: {}", e);
+    match format {
+      ArchiveFormat::Tar => tar::Archive::new(bytes).unpack(dest).map_err(map_io),
+      ArchiveFormat::TarGz => tar::Archive::new(flate2::read::GzDecoder::new(bytes))
+        .unpack(dest)
+        .map_err(map_io),
+      ArchiveFormat::TarZst => {
+        let decoder = zstd::stream::read::Decoder::new(bytes).map_err(map_io)?;
+        tar::Archive::new(decoder).unpack(dest).map_err(map_io)
+      }
+      ArchiveFormat::Zip => {
+        let mut zip = zip::ZipArchive::new(std::io::Cursor::new(bytes))
+          .map_err(|e| format!("Failed to read zip archive: {}", e))?;
+        zip
+          .extract(dest)
+          .map_err(|e| format!("Failed to extract zip archive: {}", e))
+      }
+    }
+  }
+
   fn download(
     core: Arc<Core>,
     url: Url,
     file_name: String,
     expected_digest: hashing::Digest,
+    codec: Codec,
   ) -> BoxFuture<(), String> {
-    // TODO: Retry failures
-    core
-      .http_client
-      .get(url.clone())
-      .send()
-      .compat()
-      .map_err(|err| format!("Error downloading file: {}", err))
-      .and_then(move |response| {
-        // Handle common HTTP errors.
-        if response.status().is_server_error() {
-          Err(format!(
-            "Server error ({}) downloading file {} from {}",
-            response.status().as_str(),
-            file_name,
-            url,
-          ))
-        } else if response.status().is_client_error() {
-          Err(format!(
-            "Client error ({}) downloading file {} from {}",
-            response.status().as_str(),
-            file_name,
-            url,
+    // A writer that caps how many (post-decompression) bytes it will accept.
+    struct SizeLimiter<W: std::io::Write> {
+      writer: W,
+      written: usize,
+      size_limit: usize,
+    }
+
+    impl<W: std::io::Write> Write for SizeLimiter<W> {
+      fn write(&mut self, buf: &[u8]) -> Result<usize, std::io::Error> {
+        let new_size = self.written + buf.len();
+        if new_size > self.size_limit {
+          Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "Downloaded file was larger than expected digest",
           ))
         } else {
-          Ok(response)
+          self.written = new_size;
+          self.writer.write_all(buf)?;
+          Ok(buf.len())
         }
-      })
-      .and_then(move |response| {
-        struct SizeLimiter<W: std::io::Write> {
-          writer: W,
-          written: usize,
-          size_limit: usize,
+      }
+
+      fn flush(&mut self) -> Result<(), std::io::Error> {
+        self.writer.flush()
+      }
+    }
+
+    // Errors that should trigger a retry (transient network/server problems) vs. those that
+    // should fail immediately (client errors, digest mismatch, oversized responses).
+    enum DownloadError {
+      Transient(String),
+      Fatal(String),
+    }
+
+    Box::pin(async move {
+      use tokio::io::AsyncReadExt;
+      use tokio_util::io::StreamReader;
+
+      // Range resumption is only possible for the identity codec, since a decompressor cannot be
+      // resumed partway through its input stream.
+      let can_resume = codec == Codec::Identity;
+
+      // Persist the hasher (and the count of bytes it has seen) across attempts so that a resumed
+      // download continues feeding the same SizeLimiter/hasher rather than restarting.
+      let mut hasher = hashing::WriterHasher::new(SizeLimiter {
+        writer: bytes::BytesMut::with_capacity(expected_digest.1).writer(),
+        written: 0,
+        size_limit: expected_digest.1,
+      });
+      let mut written: usize = 0;
+      // The span of the node whose workunit the byte-progress updates below should attach to.
+      let span_id = workunit_store::get_parent_id();
+
+      let mut attempt: u32 = 0;
+      let (actual_digest, buf) = loop {
+        let mut request = core.http_client.get(url.clone());
+        if can_resume && written > 0 {
+          request = request.header(reqwest::header::RANGE, format!("bytes={}-", written));
         }
 
-        impl<W: std::io::Write> Write for SizeLimiter<W> {
-          fn write(&mut self, buf: &[u8]) -> Result<usize, std::io::Error> {
-            let new_size = self.written + buf.len();
-            if new_size > self.size_limit {
-              Err(std::io::Error::new(
-                std::io::ErrorKind::InvalidData,
-                "Downloaded file was larger than expected digest",
-              ))
-            } else {
-              self.written = new_size;
-              self.writer.write_all(buf)?;
-              Ok(buf.len())
-            }
+        let result: Result<Option<(hashing::Digest, bytes::Bytes)>, DownloadError> = async {
+          let response = request
+            .send()
+            .await
+            .map_err(|err| DownloadError::Transient(format!("Error downloading file: {}", err)))?;
+
+          if response.status().is_server_error() {
+            return Err(DownloadError::Transient(format!(
+              "Server error ({}) downloading file {} from {}",
+              response.status().as_str(),
+              file_name,
+              url,
+            )));
+          } else if response.status().is_client_error() {
+            return Err(DownloadError::Fatal(format!(
+              "Client error ({}) downloading file {} from {}",
+              response.status().as_str(),
+              file_name,
+              url,
+            )));
           }
 
-          fn flush(&mut self) -> Result<(), std::io::Error> {
-            self.writer.flush()
+          // If we asked to resume but the server ignored the Range (200 instead of 206), restart
+          // from zero by resetting the hasher and discarding what we had written.
+          if written > 0 && response.status() != reqwest::StatusCode::PARTIAL_CONTENT {
+            hasher = hashing::WriterHasher::new(SizeLimiter {
+              writer: bytes::BytesMut::with_capacity(expected_digest.1).writer(),
+              written: 0,
+              size_limit: expected_digest.1,
+            });
+            written = 0;
           }
-        }
 
-        let digest_and_bytes = async move {
-          let mut hasher = hashing::WriterHasher::new(SizeLimiter {
-            writer: bytes::BytesMut::with_capacity(expected_digest.1).writer(),
-            written: 0,
-            size_limit: expected_digest.1,
-          });
-
-          let mut response_stream = response.bytes_stream();
-          while let Some(next_chunk) = response_stream.next().await {
-            let chunk =
-              next_chunk.map_err(|err| format!("Error reading URL fetch response: {}", err))?;
-            hasher
-              .write_all(&chunk)
-              .map_err(|err| format!("Error hashing/capturing URL fetch response: {}", err))?;
+          let stream = response
+            .bytes_stream()
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err.to_string()));
+          let reader = tokio::io::BufReader::new(StreamReader::new(stream));
+          let mut reader: std::pin::Pin<Box<dyn tokio::io::AsyncRead + Send>> = match codec {
+            Codec::Identity => Box::pin(reader),
+            Codec::Gzip => Box::pin(async_compression::tokio::bufread::GzipDecoder::new(reader)),
+            Codec::Bzip2 => Box::pin(async_compression::tokio::bufread::BzDecoder::new(reader)),
+            Codec::Xz => Box::pin(async_compression::tokio::bufread::XzDecoder::new(reader)),
+            Codec::Zstd => Box::pin(async_compression::tokio::bufread::ZstdDecoder::new(reader)),
+          };
+
+          let mut buf = [0u8; 8192];
+          loop {
+            let read = match reader.read(&mut buf).await {
+              Ok(read) => read,
+              // A mid-stream read error is transient: we can retry and resume.
+              Err(err) => {
+                return Err(DownloadError::Transient(format!(
+                  "Error reading URL fetch response: {}",
+                  err
+                )))
+              }
+            };
+            if read == 0 {
+              break;
+            }
+            written += read;
+            // Report bytes-downloaded progress against the expected total.
+            workunit_store::update_workunit(
+              span_id.clone(),
+              Some((written as u64, expected_digest.1 as u64)),
+              format!("downloaded {}/{} bytes", written, expected_digest.1),
+            );
+            hasher.write_all(&buf[..read]).map_err(|err| {
+              // Exceeding the size limit is a hard failure, not a transient one.
+              DownloadError::Fatal(format!("Error hashing/capturing URL fetch response: {}", err))
+            })?;
+          }
+          Ok(None)
+        }
+        .await;
+
+        match result {
+          Ok(_) => {
+            // The hasher must be moved out to finish it, so rebuild a fresh empty one in its place.
+            let finished = std::mem::replace(
+              &mut hasher,
+              hashing::WriterHasher::new(SizeLimiter {
+                writer: bytes::BytesMut::new().writer(),
+                written: 0,
+                size_limit: expected_digest.1,
+              }),
+            );
+            let (digest, bytewriter) = finished.finish();
+            break (digest, bytewriter.writer.into_inner().freeze());
+          }
+          Err(DownloadError::Fatal(e)) => return Err(e),
+          Err(DownloadError::Transient(e)) => {
+            if attempt >= core.download_retries {
+              return Err(e);
+            }
+            tokio::time::sleep(backoff(core.download_retry_base_delay, attempt)).await;
+            attempt += 1;
           }
-          let (digest, bytewriter) = hasher.finish();
-          Ok((digest, bytewriter.writer.into_inner().freeze()))
-        };
-        digest_and_bytes.boxed().compat().to_boxed()
-      })
-      .and_then(move |(actual_digest, buf)| {
-        if expected_digest != actual_digest {
-          return future::err(format!(
-            "Wrong digest for downloaded file: want {:?} got {:?}",
-            expected_digest, actual_digest
-          ))
-          .to_boxed();
         }
+      };
 
-        Box::pin(async move {
-          let _ = core.store().store_file_bytes(buf, true).await?;
-          Ok(())
-        })
-        .compat()
-        .to_boxed()
-      })
-      .to_boxed()
+      if expected_digest != actual_digest {
+        return Err(format!(
+          "Wrong digest for downloaded file: want {:?} got {:?}",
+          expected_digest, actual_digest
+        ));
+      }
+
+      let _ = core.store().store_file_bytes(buf, true).await?;
+      Ok(())
+    })
+    .compat()
+    .to_boxed()
+  }
+
+  ///
+  /// Stream bytes from a non-HTTP blob source through the same decompression, size-limiting,
+  /// hashing and digest-verification pipeline used by `download`, then store the result.
+  ///
+  async fn download_via_source(
+    core: Arc<Core>,
+    source: Box<dyn BlobSource>,
+    expected_digest: hashing::Digest,
+    codec: Codec,
+  ) -> Result<(), String> {
+    use tokio::io::AsyncReadExt;
+    use tokio_util::io::StreamReader;
+
+    let stream = source
+      .stream()
+      .await?
+      .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err));
+    let reader = tokio::io::BufReader::new(StreamReader::new(stream));
+    let mut reader: std::pin::Pin<Box<dyn tokio::io::AsyncRead + Send>> = match codec {
+      Codec::Identity => Box::pin(reader),
+      Codec::Gzip => Box::pin(async_compression::tokio::bufread::GzipDecoder::new(reader)),
+      Codec::Bzip2 => Box::pin(async_compression::tokio::bufread::BzDecoder::new(reader)),
+      Codec::Xz => Box::pin(async_compression::tokio::bufread::XzDecoder::new(reader)),
+      Codec::Zstd => Box::pin(async_compression::tokio::bufread::ZstdDecoder::new(reader)),
+    };
+
+    let mut collected = bytes::BytesMut::with_capacity(expected_digest.1);
+    let mut hasher = hashing::WriterHasher::new(std::io::sink());
+    let mut buf = [0u8; 8192];
+    loop {
+      let read = reader
+        .read(&mut buf)
+        .await
+        .map_err(|err| format!("Error reading blob: {}", err))?;
+      if read == 0 {
+        break;
+      }
+      if collected.len() + read > expected_digest.1 {
+        return Err("Fetched blob was larger than expected digest".to_owned());
+      }
+      collected.extend_from_slice(&buf[..read]);
+      hasher
+        .write_all(&buf[..read])
+        .map_err(|err| format!("Error hashing blob: {}", err))?;
+    }
+
+    let (actual_digest, _) = hasher.finish();
+    if expected_digest != actual_digest {
+      return Err(format!(
+        "Wrong digest for fetched blob: want {:?} got {:?}",
+        expected_digest, actual_digest
+      ));
+    }
+
+    let _ = core.store().store_file_bytes(collected.freeze(), true).await?;
+    Ok(())
   }
 }
 
@@ -757,8 +1731,20 @@ impl WrappedNode for DownloadedFile {
     ))
     .map_err(|str| throw(&str)));
 
+    let archive_format = if externs::project_bool(&value, "auto_extract") {
+      try_future!(
+        ArchiveFormat::lift(&externs::project_str(&value, "archive_format")).map_err(|e| throw(&e))
+      )
+    } else {
+      None
+    };
+
+    let codec = try_future!(
+      Codec::lift(&externs::project_str(&value, "decompress"), &url, None).map_err(|e| throw(&e))
+    );
+
     self
-      .load_or_download(context.core, url, expected_digest)
+      .load_or_download(context.core, url, expected_digest, archive_format, codec)
       .map(Arc::new)
       .map_err(|err| throw(&err))
       .to_boxed()
@@ -771,6 +1757,209 @@ impl From<DownloadedFile> for NodeKey {
   }
 }
 
+///
+/// The compression codecs supported by the deterministic archive node.
+///
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum ArchiveCompression {
+  None,
+  Gzip,
+  Bzip2,
+}
+
+impl ArchiveCompression {
+  fn lift(value: &str) -> Result<ArchiveCompression, String> {
+    match value {
+      "" | "none" => Ok(ArchiveCompression::None),
+      "gzip" | "gz" => Ok(ArchiveCompression::Gzip),
+      "bzip2" | "bz2" => Ok(ArchiveCompression::Bzip2),
+      other => Err(format!("Unrecognized archive compression: {}", other)),
+    }
+  }
+}
+
+///
+/// A Node that produces a reproducible tar archive (optionally compressed) from the contents of a
+/// Digest, streaming the result back into the store as a new, content-addressed Digest. Because
+/// every entry's metadata is normalized (fixed mtime, zeroed uid/gid and owner/group, canonical
+/// permission bits) and entries are emitted in sorted order, identical input digests always yield
+/// byte-identical archives that are cache-shareable across machines.
+///
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct MakeArchive(pub Key);
+
+impl MakeArchive {
+  // A fixed epoch for all entry mtimes, so archives are reproducible.
+  const FIXED_MTIME: u64 = 0;
+
+  fn run(self, context: Context) -> NodeFuture<hashing::Digest> {
+    let value = externs::val_for(&self.0);
+    let digest = try_future!(lift_digest(&externs::project_ignoring_type(&value, "digest"))
+      .map_err(|e| throw(&e)));
+    let compression = try_future!(
+      ArchiveCompression::lift(&externs::project_str(&value, "compression")).map_err(|e| throw(&e))
+    );
+    let strip_prefix = externs::project_str(&value, "strip_prefix");
+    let prepend_prefix = externs::project_str(&value, "prepend_prefix");
+
+    Box::pin(async move {
+      let store = context.core.store();
+      let root = tempfile::TempDir::new()
+        .map_err(|e| throw(&format!("Failed to create a temporary directory: {}", e)))?;
+      store
+        .materialize_directory(root.path().to_owned(), digest)
+        .await
+        .map_err(|e| throw(&e))?;
+
+      // Walk the tree in a stable, sorted order so the archive is deterministic.
+      let mut entries: Vec<PathBuf> = walkdir::WalkDir::new(root.path())
+        .sort_by_file_name()
+        .min_depth(1)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .map(|e| e.path().to_owned())
+        .collect();
+      entries.sort();
+
+      let archive = Self::build_tar(
+        root.path(),
+        &entries,
+        &strip_prefix,
+        &prepend_prefix,
+        compression,
+      )
+      .map_err(|e| throw(&e))?;
+
+      store
+        .store_file_bytes(bytes::Bytes::from(archive), true)
+        .await
+        .map_err(|e| throw(&e))
+    })
+    .compat()
+    .to_boxed()
+  }
+
+  ///
+  /// Build a deterministic tar of `entries` (relative to `root`), normalizing metadata and
+  /// rewriting each path by stripping `strip_prefix` and prepending `prepend_prefix`.
+  ///
+  fn build_tar(
+    root: &Path,
+    entries: &[PathBuf],
+    strip_prefix: &str,
+    prepend_prefix: &str,
+    compression: ArchiveCompression,
+  ) -> Result<Vec<u8>, String> {
+    let mut builder = tar::Builder::new(Vec::new());
+    builder.mode(tar::HeaderMode::Deterministic);
+
+    for abs_path in entries {
+      let rel = abs_path
+        .strip_prefix(root)
+        .map_err(|e| format!("Failed to relativize archive entry: {}", e))?;
+      let rel = rel
+        .strip_prefix(strip_prefix)
+        .unwrap_or(rel)
+        .to_path_buf();
+      let archive_path = if prepend_prefix.is_empty() {
+        rel
+      } else {
+        Path::new(prepend_prefix).join(rel)
+      };
+      if archive_path.as_os_str().is_empty() {
+        continue;
+      }
+
+      let metadata =
+        std::fs::symlink_metadata(abs_path).map_err(|e| format!("Failed to stat entry: {}", e))?;
+      let mut header = tar::Header::new_gnu();
+      header.set_mtime(Self::FIXED_MTIME);
+      header.set_uid(0);
+      header.set_gid(0);
+      header
+        .set_username("")
+        .and_then(|_| header.set_groupname(""))
+        .map_err(|e| format!("Failed to normalize entry ownership: {}", e))?;
+
+      if metadata.is_dir() {
+        header.set_entry_type(tar::EntryType::Directory);
+        header.set_mode(0o755);
+        header.set_size(0);
+        header.set_cksum();
+        builder
+          .append_data(&mut header, &archive_path, std::io::empty())
+          .map_err(|e| format!("Failed to append dir to archive: {}", e))?;
+      } else if metadata.file_type().is_symlink() {
+        // Record the link target verbatim, rather than following it: following would stat/read
+        // whatever the link points at (possibly outside `root` entirely) and conflate its
+        // contents with this entry's, corrupting the archive.
+        let target = std::fs::read_link(abs_path)
+          .map_err(|e| format!("Failed to read symlink target: {}", e))?;
+        header.set_entry_type(tar::EntryType::Symlink);
+        header.set_mode(0o777);
+        header.set_size(0);
+        header
+          .set_link_name(&target)
+          .map_err(|e| format!("Failed to set symlink target in archive: {}", e))?;
+        header.set_cksum();
+        builder
+          .append_data(&mut header, &archive_path, std::io::empty())
+          .map_err(|e| format!("Failed to append symlink to archive: {}", e))?;
+      } else {
+        use std::os::unix::fs::PermissionsExt;
+        let is_executable = metadata.permissions().mode() & 0o111 != 0;
+        header.set_mode(if is_executable { 0o755 } else { 0o644 });
+        let contents =
+          std::fs::File::open(abs_path).map_err(|e| format!("Failed to read entry: {}", e))?;
+        header.set_size(metadata.len());
+        header.set_cksum();
+        builder
+          .append_data(&mut header, &archive_path, contents)
+          .map_err(|e| format!("Failed to append file to archive: {}", e))?;
+      }
+    }
+
+    let tar_bytes = builder
+      .into_inner()
+      .map_err(|e| format!("Failed to finish archive: {}", e))?;
+
+    match compression {
+      ArchiveCompression::None => Ok(tar_bytes),
+      ArchiveCompression::Gzip => {
+        use std::io::Write;
+        // mtime 0 keeps the gzip header reproducible.
+        let mut encoder = flate2::write::GzEncoder::new(
+          Vec::new(),
+          flate2::Compression::default(),
+        );
+        encoder
+          .write_all(&tar_bytes)
+          .map_err(|e| format!("Failed to gzip archive: {}", e))?;
+        encoder
+          .finish()
+          .map_err(|e| format!("Failed to finish gzip archive: {}", e))
+      }
+      ArchiveCompression::Bzip2 => {
+        use std::io::Write;
+        let mut encoder =
+          bzip2::write::BzEncoder::new(Vec::new(), bzip2::Compression::default());
+        encoder
+          .write_all(&tar_bytes)
+          .map_err(|e| format!("Failed to bzip2 archive: {}", e))?;
+        encoder
+          .finish()
+          .map_err(|e| format!("Failed to finish bzip2 archive: {}", e))
+      }
+    }
+  }
+}
+
+impl From<MakeArchive> for NodeKey {
+  fn from(n: MakeArchive) -> Self {
+    NodeKey::MakeArchive(n)
+  }
+}
+
 #[derive(Clone, Eq, Hash, PartialEq)]
 pub struct Task {
   params: Params,
@@ -856,14 +2045,39 @@ impl Task {
       future::result(externs::generator_send(&generator, &input)).and_then(move |response| {
         match response {
           externs::GeneratorResponse::Get(get) => {
-            Self::gen_get(&context, &params, &entry, vec![get])
-              .map(|vs| future::Loop::Continue(vs.into_iter().next().unwrap()))
-              .to_boxed()
+            // Cooperative cancellation/reprioritization at the yield point: abort cleanly if the
+            // node's token has been cancelled, or park behind the priority gate if it has been
+            // deprioritized so that higher-priority nodes run first.
+            Box::pin(async move {
+              if context.node_token().is_cancelled() {
+                return Err(Failure::Cancelled);
+              }
+              context.node_token().priority_gate().await;
+              let span_id = workunit_store::get_parent_id();
+              workunit_store::update_workunit(span_id, None, "awaiting 1 dependency".to_owned());
+              let vs = Self::gen_get(&context, &params, &entry, vec![get]).compat().await?;
+              Ok(future::Loop::Continue(vs.into_iter().next().unwrap()))
+            })
+            .compat()
+            .to_boxed()
           }
           externs::GeneratorResponse::GetMulti(gets) => {
-            Self::gen_get(&context, &params, &entry, gets)
-              .map(|vs| future::Loop::Continue(externs::store_tuple(&vs)))
-              .to_boxed()
+            Box::pin(async move {
+              if context.node_token().is_cancelled() {
+                return Err(Failure::Cancelled);
+              }
+              context.node_token().priority_gate().await;
+              let span_id = workunit_store::get_parent_id();
+              workunit_store::update_workunit(
+                span_id,
+                None,
+                format!("awaiting {} dependencies", gets.len()),
+              );
+              let vs = Self::gen_get(&context, &params, &entry, gets).compat().await?;
+              Ok(future::Loop::Continue(externs::store_tuple(&vs)))
+            })
+            .compat()
+            .to_boxed()
           }
           externs::GeneratorResponse::Break(val) => future::ok(future::Loop::Break(val)).to_boxed(),
         }
@@ -951,6 +2165,7 @@ impl NodeVisualizer<NodeKey> for Visualizer {
       None => "white".to_string(),
       Some(Err(Failure::Throw(..))) => "4".to_string(),
       Some(Err(Failure::Invalidated)) => "12".to_string(),
+      Some(Err(Failure::Cancelled)) => "9".to_string(),
       Some(Ok(_)) => {
         let viz_colors_len = self.viz_colors.len();
         self
@@ -969,6 +2184,7 @@ impl NodeTracer<NodeKey> for Tracer {
   fn is_bottom(result: Option<Result<NodeResult, Failure>>) -> bool {
     match result {
       Some(Err(Failure::Invalidated)) => false,
+      Some(Err(Failure::Cancelled)) => false,
       Some(Err(Failure::Throw(..))) => false,
       Some(Ok(_)) => true,
       None => {
@@ -994,6 +2210,7 @@ impl NodeTracer<NodeKey> for Tracer {
           .join("\n")
       ),
       Some(Err(Failure::Invalidated)) => "Invalidated".to_string(),
+      Some(Err(Failure::Cancelled)) => "Cancelled".to_string(),
     }
   }
 }
@@ -1005,6 +2222,7 @@ impl NodeTracer<NodeKey> for Tracer {
 pub enum NodeKey {
   DigestFile(DigestFile),
   DownloadedFile(DownloadedFile),
+  MakeArchive(MakeArchive),
   MultiPlatformExecuteProcess(Box<MultiPlatformExecuteProcess>),
   ReadLink(ReadLink),
   Scandir(Scandir),
@@ -1018,6 +2236,7 @@ impl NodeKey {
     match self {
       &NodeKey::MultiPlatformExecuteProcess(..) => "ProcessResult".to_string(),
       &NodeKey::DownloadedFile(..) => "DownloadedFile".to_string(),
+      &NodeKey::MakeArchive(..) => "Digest".to_string(),
       &NodeKey::Select(ref s) => format!("{}", s.product),
       &NodeKey::Task(ref s) => format!("{}", s.product),
       &NodeKey::Snapshot(..) => "Snapshot".to_string(),
@@ -1041,6 +2260,7 @@ impl NodeKey {
       | &NodeKey::Select { .. }
       | &NodeKey::Snapshot { .. }
       | &NodeKey::Task { .. }
+      | &NodeKey::MakeArchive { .. }
       | &NodeKey::DownloadedFile { .. } => None,
     }
   }
@@ -1074,6 +2294,8 @@ impl Node for NodeKey {
         desc,
         display,
         blocked: false,
+        // Incremental progress, populated by node implementations via update_workunit.
+        progress: None,
       };
 
       context
@@ -1100,6 +2322,7 @@ impl Node for NodeKey {
         Ok(()) => match self {
           NodeKey::DigestFile(n) => n.run(context).map(NodeResult::from).compat().await,
           NodeKey::DownloadedFile(n) => n.run(context).map(NodeResult::from).compat().await,
+          NodeKey::MakeArchive(n) => n.run(context).map(NodeResult::from).compat().await,
           NodeKey::MultiPlatformExecuteProcess(n) => {
             n.run(context).map(NodeResult::from).compat().await
           }
@@ -1148,6 +2371,7 @@ impl Node for NodeKey {
       NodeKey::MultiPlatformExecuteProcess(mp_epr) => mp_epr.0.user_facing_name(),
       NodeKey::DigestFile(..) => None,
       NodeKey::DownloadedFile(..) => None,
+      NodeKey::MakeArchive(..) => None,
       NodeKey::ReadLink(..) => None,
       NodeKey::Scandir(..) => None,
       NodeKey::Select(..) => None,
@@ -1160,6 +2384,7 @@ impl Display for NodeKey {
     match self {
       &NodeKey::DigestFile(ref s) => write!(f, "DigestFile({:?})", s.0),
       &NodeKey::DownloadedFile(ref s) => write!(f, "DownloadedFile({:?})", s.0),
+      &NodeKey::MakeArchive(ref s) => write!(f, "MakeArchive({:?})", s.0),
       &NodeKey::MultiPlatformExecuteProcess(ref s) => {
         write!(f, "MultiPlatformExecuteProcess({:?}", s.0)
       }
@@ -1307,3 +2532,72 @@ impl TryFrom<NodeResult> for Arc<DirectoryListing> {
     }
   }
 }
+
+#[cfg(test)]
+mod content_defined_chunking_tests {
+  use super::{gear_table, next_chunk_len};
+
+  #[test]
+  fn next_chunk_len_returns_whole_input_below_min() {
+    let gear = gear_table();
+    let data = vec![7u8; 10];
+    assert_eq!(next_chunk_len(&data, &gear, 16, 64, 128), 10);
+  }
+
+  #[test]
+  fn next_chunk_len_never_exceeds_max() {
+    let gear = gear_table();
+    // All-zero input never trips a mask boundary, so the cut should land exactly at `max`.
+    let data = vec![0u8; 1024];
+    assert_eq!(next_chunk_len(&data, &gear, 8, 64, 256), 256);
+  }
+
+  #[test]
+  fn next_chunk_len_is_stable_across_calls() {
+    // Chunk boundaries must be deterministic across processes/runs for content-defined chunking
+    // to deduplicate; the same input and gear table must always produce the same cut point.
+    let gear = gear_table();
+    let data: Vec<u8> = (0..512u32).map(|i| (i % 251) as u8).collect();
+    let first = next_chunk_len(&data, &gear, 16, 64, 256);
+    let second = next_chunk_len(&data, &gear, 16, 64, 256);
+    assert_eq!(first, second);
+  }
+}
+
+#[cfg(test)]
+mod download_backoff_tests {
+  use super::backoff;
+  use std::time::Duration;
+
+  #[test]
+  fn backoff_grows_with_attempt_number() {
+    // Jitter makes any single pair of samples noisy, so compare against the deterministic ceiling
+    // (the pre-jitter capped value) rather than another jittered sample. Attempt 4's floor (half of
+    // its 1600ms ceiling) lies above attempt 0's ceiling, so the delay does grow.
+    let base = Duration::from_millis(100);
+    assert!(backoff(base, 0) <= Duration::from_millis(100));
+    assert!(backoff(base, 1) <= Duration::from_millis(200));
+    assert!(backoff(base, 4) <= Duration::from_millis(1_600));
+    assert!(backoff(base, 4) >= Duration::from_millis(800));
+  }
+
+  #[test]
+  fn backoff_is_capped_regardless_of_attempt_number() {
+    let base = Duration::from_millis(1_000);
+    for attempt in 0..20 {
+      assert!(backoff(base, attempt) <= Duration::from_millis(5_000));
+    }
+  }
+
+  #[test]
+  fn backoff_never_goes_negative_or_exceeds_its_half_plus_jitter() {
+    let base = Duration::from_millis(100);
+    for attempt in 0..6 {
+      let capped = (base.as_millis() as u64).saturating_mul(1u64 << attempt.min(5)).min(5_000);
+      let half = capped / 2;
+      let delay = backoff(base, attempt);
+      assert!(delay.as_millis() as u64 >= half);
+      assert!(delay.as_millis() as u64 <= capped);
+    }
+  }
+}